@@ -0,0 +1,596 @@
+/*!
+
+    age v1 file encryption (https://age-encryption.org/v1), so that
+        exported lockers can be read by the age tool and vice versa
+
+    A random 16 byte file key is wrapped in one stanza per recipient,
+        the header is authenticated with an HMAC keyed from the file
+        key, and the payload is encrypted with ChaCha20-Poly1305 in
+        64KiB chunks (STREAM)
+
+    age-encryption.org/v1
+    -> X25519 <ephemeral share>
+    <wrapped file key>
+    -> scrypt <salt> <log2 N>
+    <wrapped file key>
+    --- <header MAC>
+    <payload nonce (16)><chunk>...<last chunk>
+
+    X25519 recipients are bech32 encoded as age1..., and identities
+        as AGE-SECRET-KEY-1...
+
+*/
+
+use openssl::
+{
+    pkey::{PKey, Id, Private, Public},
+    symm::{Cipher, encrypt_aead, decrypt_aead},
+    rand::rand_bytes, derive::Deriver, pkcs5::scrypt,
+    sign::Signer, hash::MessageDigest, memcmp
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD_NO_PAD};
+
+use crate::
+{
+    crypto::hkdf_sha256,
+    error::AgeError,
+    util::read_file_utf8
+};
+
+const INTRO: &str = "age-encryption.org/v1";
+const X25519_LABEL: &[u8] = b"age-encryption.org/v1/X25519";
+const SCRYPT_LABEL: &[u8] = b"age-encryption.org/v1/scrypt";
+const RECIPIENT_HRP: &str = "age";
+const IDENTITY_HRP: &str = "age-secret-key-";
+
+const FILE_KEY_LENGTH: usize = 16;
+const WRAPPED_KEY_LENGTH: usize = FILE_KEY_LENGTH+16;
+const PAYLOAD_NONCE_LENGTH: usize = 16;
+const CHUNK_LENGTH: usize = 64*1024;
+const TAG_LENGTH: usize = 16;
+const COLUMNS: usize = 64;
+
+pub const SCRYPT_WORK_FACTOR: u8 = 18;
+const MAX_SCRYPT_WORK_FACTOR: u8 = 22;
+
+pub enum AgeRecipient
+{
+    X25519(PKey<Public>),
+    // passphrase, log2 of the scrypt work factor
+    Scrypt(String, u8)
+}
+
+pub enum AgeIdentity
+{
+    X25519(PKey<Private>),
+    Scrypt(String)
+}
+
+struct Stanza
+{
+    kind: String,
+    args: Vec<String>,
+    body: Vec<u8>
+}
+
+struct Header<'a>
+{
+    stanzas: Vec<Stanza>,
+    // up to (and including) "---"
+    authenticated: &'a [u8],
+    mac: Vec<u8>,
+    payload_offset: usize
+}
+
+fn error(why: &str) -> AgeError
+{
+    AgeError { why: why.to_string() }
+}
+
+pub fn is_age(data: &[u8]) -> bool
+{
+    data.starts_with(format!("{}\n", INTRO).as_bytes())
+}
+
+/*
+    ChaCha20-Poly1305 with a zero nonce, the key is only ever used once
+*/
+fn seal_key(key: &[u8], data: &[u8]) -> Vec<u8>
+{
+    let mut tag = [0; TAG_LENGTH];
+    let mut sealed = encrypt_aead(Cipher::chacha20_poly1305(), key, Some(&[0; 12]), &[], data, &mut tag).unwrap();
+    sealed.extend_from_slice(&tag);
+    sealed
+}
+
+fn open_key(key: &[u8], sealed: &[u8]) -> Option<Vec<u8>>
+{
+    if sealed.len() != WRAPPED_KEY_LENGTH { return None; }
+    let (cipher_text, tag) = sealed.split_at(FILE_KEY_LENGTH);
+    decrypt_aead(Cipher::chacha20_poly1305(), key, Some(&[0; 12]), &[], cipher_text, tag).ok()
+}
+
+fn scrypt_key(pass: &str, salt: &[u8], log_n: u8) -> Result<Vec<u8>, AgeError>
+{
+    let mut key = vec![0; 32];
+    let n: u64 = 1 << log_n;
+    // openssl needs 128*r*(N+2) bytes, plus 128*r*p
+    match scrypt(pass.as_bytes(), &[SCRYPT_LABEL, salt].concat(), n, 8, 1, 128*8*(n+3), &mut key)
+    {
+        Ok(_) => Ok(key),
+        Err(e) => Err(AgeError { why: format!("scrypt failed: {}", e) })
+    }
+}
+
+fn x25519_key(ephemeral: &PKey<Private>, peer: &PKey<Public>, share: &[u8], recipient: &[u8]) -> Option<Vec<u8>>
+{
+    let mut deriver = Deriver::new(ephemeral).ok()?;
+    deriver.set_peer(peer).ok()?;
+    // openssl refuses an all zero shared secret (a low order point)
+    let shared = deriver.derive_to_vec().ok()?;
+    Some(hkdf_sha256(&[share, recipient].concat(), &shared, X25519_LABEL, 32))
+}
+
+fn wrap(recipient: &AgeRecipient, file_key: &[u8]) -> Result<Stanza, AgeError>
+{
+    match recipient
+    {
+        AgeRecipient::X25519(public) =>
+        {
+            let ephemeral = PKey::generate_x25519().unwrap();
+            let share = ephemeral.raw_public_key().unwrap();
+            let key = match x25519_key(&ephemeral, public, &share, &public.raw_public_key().unwrap())
+            {
+                Some(k) => k,
+                None => { return Err(error("X25519 key agreement failed")); }
+            };
+            Ok(Stanza { kind: "X25519".to_string(), args: vec![STANDARD_NO_PAD.encode(&share)], body: seal_key(&key, file_key) })
+        },
+        AgeRecipient::Scrypt(pass, log_n) =>
+        {
+            let mut salt = [0; 16];
+            rand_bytes(&mut salt).unwrap();
+            let key = scrypt_key(pass, &salt, *log_n)?;
+            Ok(Stanza { kind: "scrypt".to_string(), args: vec![STANDARD_NO_PAD.encode(salt), log_n.to_string()], body: seal_key(&key, file_key) })
+        }
+    }
+}
+
+fn unwrap(identity: &AgeIdentity, stanza: &Stanza) -> Result<Option<Vec<u8>>, AgeError>
+{
+    match (identity, stanza.kind.as_str())
+    {
+        (AgeIdentity::X25519(private), "X25519") =>
+        {
+            if stanza.args.len() != 1 { return Err(error("malformed X25519 stanza")); }
+
+            let share = match STANDARD_NO_PAD.decode(&stanza.args[0])
+            {
+                Ok(s) if s.len() == 32 => s,
+                _ => { return Err(error("malformed X25519 share")); }
+            };
+
+            let peer = match PKey::public_key_from_raw_bytes(&share, Id::X25519)
+            {
+                Ok(p) => p,
+                Err(_) => { return Err(error("malformed X25519 share")); }
+            };
+
+            match x25519_key(private, &peer, &share, &private.raw_public_key().unwrap())
+            {
+                Some(key) => Ok(open_key(&key, &stanza.body)),
+                None => Err(error("X25519 key agreement failed"))
+            }
+        },
+        (AgeIdentity::Scrypt(pass), "scrypt") =>
+        {
+            if stanza.args.len() != 2 { return Err(error("malformed scrypt stanza")); }
+
+            let salt = match STANDARD_NO_PAD.decode(&stanza.args[0])
+            {
+                Ok(s) if s.len() == 16 => s,
+                _ => { return Err(error("malformed scrypt salt")); }
+            };
+
+            let log_n = match stanza.args[1].parse::<u8>()
+            {
+                Ok(n) if n > 0 && n <= MAX_SCRYPT_WORK_FACTOR && n.to_string() == stanza.args[1] => n,
+                _ => { return Err(AgeError { why: format!("unsupported scrypt work factor {}", stanza.args[1]) }); }
+            };
+
+            let key = scrypt_key(pass, &salt, log_n)?;
+            Ok(open_key(&key, &stanza.body))
+        },
+        _ => Ok(None)
+    }
+}
+
+fn header_mac(file_key: &[u8], header: &[u8]) -> Vec<u8>
+{
+    let key = PKey::hmac(&hkdf_sha256(&[], file_key, b"header", 32)).unwrap();
+    let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+    signer.update(header).unwrap();
+    signer.sign_to_vec().unwrap()
+}
+
+fn chunk_nonce(counter: u64, last: bool) -> [u8; 12]
+{
+    let mut nonce = [0; 12];
+    nonce[3..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+pub fn encrypt(recipients: &[AgeRecipient], data: &[u8]) -> Result<Vec<u8>, AgeError>
+{
+    if recipients.is_empty()
+    {
+        return Err(error("no recipients"));
+    }
+
+    if recipients.len() > 1 && recipients.iter().any(|r| matches!(r, AgeRecipient::Scrypt(_, _)))
+    {
+        return Err(error("a passphrase must be the only recipient"));
+    }
+
+    let mut file_key = [0; FILE_KEY_LENGTH];
+    rand_bytes(&mut file_key).unwrap();
+
+    let mut header = format!("{}\n", INTRO);
+
+    for recipient in recipients
+    {
+        let stanza = wrap(recipient, &file_key)?;
+        header.push_str(&format!("-> {} {}\n", stanza.kind, stanza.args.join(" ")));
+
+        let body = STANDARD_NO_PAD.encode(&stanza.body);
+        for line in body.as_bytes().chunks(COLUMNS)
+        {
+            header.push_str(std::str::from_utf8(line).unwrap());
+            header.push('\n');
+        }
+        // the last line of a body is always short, possibly empty
+        if body.len() % COLUMNS == 0 { header.push('\n'); }
+    }
+
+    header.push_str("---");
+    let mac = header_mac(&file_key, header.as_bytes());
+    header.push_str(&format!(" {}\n", STANDARD_NO_PAD.encode(mac)));
+
+    let mut nonce = [0; PAYLOAD_NONCE_LENGTH];
+    rand_bytes(&mut nonce).unwrap();
+    let payload_key = hkdf_sha256(&nonce, &file_key, b"payload", 32);
+
+    let mut out = header.into_bytes();
+    out.extend_from_slice(&nonce);
+
+    let chunks: Vec<&[u8]> = match data.is_empty()
+    {
+        true => vec![&[]],
+        false => data.chunks(CHUNK_LENGTH).collect()
+    };
+
+    for (i, chunk) in chunks.iter().enumerate()
+    {
+        let mut tag = [0; TAG_LENGTH];
+        let nonce = chunk_nonce(i as u64, i+1 == chunks.len());
+        out.extend(encrypt_aead(Cipher::chacha20_poly1305(), &payload_key, Some(&nonce), &[], chunk, &mut tag).unwrap());
+        out.extend_from_slice(&tag);
+    }
+
+    Ok(out)
+}
+
+fn parse_header(data: &[u8]) -> Result<Header<'_>, AgeError>
+{
+    if !is_age(data)
+    {
+        return Err(error("not an age file"));
+    }
+
+    let mut stanzas: Vec<Stanza> = Vec::new();
+    let mut offset = INTRO.len()+1;
+
+    let next_line = |offset: &mut usize| -> Result<String, AgeError>
+    {
+        let end = match data[*offset..].iter().position(|b| *b == b'\n')
+        {
+            Some(e) => *offset+e,
+            None => { return Err(error("truncated header")); }
+        };
+        let line = match std::str::from_utf8(&data[*offset..end])
+        {
+            Ok(l) => l.to_string(),
+            Err(_) => { return Err(error("header is not ASCII")); }
+        };
+        *offset = end+1;
+        Ok(line)
+    };
+
+    loop
+    {
+        let start = offset;
+        let line = next_line(&mut offset)?;
+
+        if let Some(mac) = line.strip_prefix("--- ")
+        {
+            let mac = match STANDARD_NO_PAD.decode(mac)
+            {
+                Ok(m) if m.len() == 32 => m,
+                _ => { return Err(error("malformed header MAC")); }
+            };
+            return Ok(Header { stanzas, authenticated: &data[0..start+3], mac, payload_offset: offset });
+        }
+
+        let args: Vec<String> = match line.strip_prefix("-> ")
+        {
+            Some(a) => a.split(' ').map(|s| s.to_string()).collect(),
+            None => { return Err(AgeError { why: format!("unexpected header line {}", line) }); }
+        };
+
+        if args.iter().any(|a| a.is_empty())
+        {
+            return Err(error("empty stanza argument"));
+        }
+
+        let mut body = String::new();
+        loop
+        {
+            let line = next_line(&mut offset)?;
+            if line.len() > COLUMNS { return Err(error("stanza body line too long")); }
+            body.push_str(&line);
+            if line.len() < COLUMNS { break; }
+        }
+
+        let body = match STANDARD_NO_PAD.decode(&body)
+        {
+            Ok(b) => b,
+            Err(_) => { return Err(error("malformed stanza body")); }
+        };
+
+        stanzas.push(Stanza { kind: args[0].clone(), args: args[1..].to_vec(), body });
+    }
+}
+
+pub fn decrypt(identities: &[AgeIdentity], data: &[u8]) -> Result<Vec<u8>, AgeError>
+{
+    let Header { stanzas, authenticated, mac, payload_offset: offset } = parse_header(data)?;
+
+    if stanzas.iter().any(|s| s.kind == "scrypt") && stanzas.len() != 1
+    {
+        return Err(error("a scrypt stanza must be the only stanza"));
+    }
+
+    let mut file_key: Option<Vec<u8>> = None;
+
+    'search: for identity in identities
+    {
+        for stanza in &stanzas
+        {
+            if let Some(key) = unwrap(identity, stanza)?
+            {
+                file_key = Some(key);
+                break 'search;
+            }
+        }
+    }
+
+    let file_key = match file_key
+    {
+        Some(k) => k,
+        None => { return Err(error("no identity matches any recipient")); }
+    };
+
+    if !memcmp::eq(&header_mac(&file_key, authenticated), &mac)
+    {
+        return Err(error("header MAC does not match"));
+    }
+
+    if data.len() < offset+PAYLOAD_NONCE_LENGTH+TAG_LENGTH
+    {
+        return Err(error("truncated payload"));
+    }
+
+    let nonce = &data[offset..offset+PAYLOAD_NONCE_LENGTH];
+    let payload_key = hkdf_sha256(nonce, &file_key, b"payload", 32);
+    let chunks: Vec<&[u8]> = data[offset+PAYLOAD_NONCE_LENGTH..].chunks(CHUNK_LENGTH+TAG_LENGTH).collect();
+
+    let mut plain: Vec<u8> = Vec::new();
+
+    for (i, chunk) in chunks.iter().enumerate()
+    {
+        let last = i+1 == chunks.len();
+
+        if chunk.len() < TAG_LENGTH || (chunk.len() == TAG_LENGTH && (i > 0 || !last))
+        {
+            return Err(error("truncated or empty payload chunk"));
+        }
+
+        let (cipher_text, tag) = chunk.split_at(chunk.len()-TAG_LENGTH);
+        match decrypt_aead(Cipher::chacha20_poly1305(), &payload_key, Some(&chunk_nonce(i as u64, last)), &[], cipher_text, tag)
+        {
+            Ok(p) => plain.extend(p),
+            Err(_) => { return Err(AgeError { why: format!("payload chunk {} failed to decrypt", i) }); }
+        }
+    }
+
+    Ok(plain)
+}
+
+/*
+    age1... for an X25519 public key
+*/
+pub fn recipient_string(public: &PKey<Public>) -> Result<String, AgeError>
+{
+    match public.raw_public_key()
+    {
+        Ok(raw) if public.id() == Id::X25519 => Ok(bech32_encode(RECIPIENT_HRP, &raw)),
+        _ => Err(error("age recipients must be X25519 keys"))
+    }
+}
+
+pub fn parse_recipient(s: &str) -> Result<PKey<Public>, AgeError>
+{
+    match bech32_decode(s)
+    {
+        Some((hrp, data)) if hrp == RECIPIENT_HRP && data.len() == 32 && s == s.to_lowercase() =>
+        {
+            match PKey::public_key_from_raw_bytes(&data, Id::X25519)
+            {
+                Ok(p) => Ok(p),
+                Err(e) => Err(AgeError { why: format!("invalid recipient {}: {}", s, e) })
+            }
+        },
+        _ => Err(AgeError { why: format!("invalid recipient {}", s) })
+    }
+}
+
+/*
+    AGE-SECRET-KEY-1... for an X25519 private key
+*/
+pub fn identity_string(private: &PKey<Private>) -> Result<String, AgeError>
+{
+    match private.raw_private_key()
+    {
+        Ok(raw) if private.id() == Id::X25519 => Ok(bech32_encode(IDENTITY_HRP, &raw).to_uppercase()),
+        _ => Err(error("age identities must be X25519 keys"))
+    }
+}
+
+pub fn parse_identity(s: &str) -> Result<PKey<Private>, AgeError>
+{
+    match bech32_decode(s)
+    {
+        Some((hrp, data)) if hrp == IDENTITY_HRP && data.len() == 32 && s == s.to_uppercase() =>
+        {
+            match PKey::private_key_from_raw_bytes(&data, Id::X25519)
+            {
+                Ok(p) => Ok(p),
+                Err(e) => Err(AgeError { why: format!("invalid identity: {}", e) })
+            }
+        },
+        _ => Err(error("invalid identity"))
+    }
+}
+
+/*
+    Identities in an age identity file, one per line, ignoring
+        empty lines and # comments
+*/
+pub fn read_identities(path: &str) -> Result<Vec<PKey<Private>>, AgeError>
+{
+    let data = match read_file_utf8(path)
+    {
+        Ok(d) => d,
+        Err(e) => { return Err(AgeError { why: format!("{}", e) }); }
+    };
+
+    let mut identities: Vec<PKey<Private>> = Vec::new();
+
+    for line in data.lines().map(|l| l.trim()).filter(|l| !l.is_empty() && !l.starts_with('#'))
+    {
+        identities.push(parse_identity(line)?);
+    }
+
+    Ok(identities)
+}
+
+/*
+    Bech32 (BIP 173), without the 90 character limit as in age
+*/
+const CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn polymod(values: &[u8]) -> u32
+{
+    let generator = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut chk: u32 = 1;
+    for v in values
+    {
+        let top = chk >> 25;
+        chk = (chk & 0x1ffffff) << 5 ^ *v as u32;
+        for (i, g) in generator.iter().enumerate()
+        {
+            if (top >> i) & 1 == 1 { chk ^= g; }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8>
+{
+    let mut out: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    out.push(0);
+    out.extend(hrp.bytes().map(|b| b & 31));
+    out
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>>
+{
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let mut out: Vec<u8> = Vec::new();
+    let max = (1 << to) - 1;
+
+    for v in data
+    {
+        if (*v as u32) >> from != 0 { return None; }
+        acc = (acc << from) | *v as u32;
+        bits += from;
+        while bits >= to
+        {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+
+    if pad
+    {
+        if bits > 0 { out.push(((acc << (to - bits)) & max) as u8); }
+    }
+    else if bits >= from || ((acc << (to - bits)) & max) != 0
+    {
+        return None;
+    }
+
+    Some(out)
+}
+
+pub fn bech32_encode(hrp: &str, data: &[u8]) -> String
+{
+    let values = convert_bits(data, 8, 5, true).unwrap();
+    let checksum = polymod(&[hrp_expand(hrp), values.clone(), vec![0; 6]].concat()) ^ 1;
+
+    let mut out = format!("{}1", hrp);
+    for v in values.iter().cloned().chain((0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8))
+    {
+        out.push(CHARSET[v as usize] as char);
+    }
+    out
+}
+
+/*
+    The (lower case) human readable part and data of a bech32 string
+*/
+pub fn bech32_decode(s: &str) -> Option<(String, Vec<u8>)>
+{
+    if s.to_lowercase() != s && s.to_uppercase() != s { return None; }
+    let s = s.to_lowercase();
+
+    let split = s.rfind('1')?;
+    if split == 0 || split+7 > s.len() { return None; }
+    let (hrp, rest) = (&s[0..split], &s[split+1..]);
+
+    if hrp.bytes().any(|b| !(33..=126).contains(&b)) { return None; }
+
+    let mut values: Vec<u8> = Vec::new();
+    for c in rest.bytes()
+    {
+        values.push(CHARSET.iter().position(|x| *x == c)? as u8);
+    }
+
+    if polymod(&[hrp_expand(hrp), values.clone()].concat()) != 1 { return None; }
+
+    let data = convert_bits(&values[0..values.len()-6], 5, 8, false)?;
+    Some((hrp.to_string(), data))
+}
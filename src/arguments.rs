@@ -1,9 +1,14 @@
+use std::path::Path;
+
 use regex::Regex;
 
 use crate::
 {
-    util::{find_file_in_dir, warning}, 
-    error::{NoSuchFileError, ArgumentError}
+    util::{find_file_in_dir, find_files_in_dir, warning, dump_bytes}, 
+    error::{NoSuchFileError, ArgumentError},
+    crypto::{build_public, public_key_path, fingerprint, PassphraseKdf},
+    file::Locker,
+    keyring::Keyring
 };
 
 pub fn extract_pass(args: &mut Vec<String>) -> Option<String>
@@ -29,10 +34,48 @@ pub fn extract_pass(args: &mut Vec<String>) -> Option<String>
     }
 }
 
+/*
+    -passphrase (scrypt) or --kdf scrypt|pbkdf2, for a new locker
+        whose key is derived from a passphrase
+*/
+pub fn extract_kdf(args: &mut Vec<String>) -> Result<Option<PassphraseKdf>, ArgumentError>
+{
+    let mut kdf: Option<PassphraseKdf> = None;
+
+    if args.iter().any(|x| x == "-passphrase")
+    {
+        let i = args.iter().position(|x| x == "-passphrase").unwrap();
+        args.remove(i);
+        kdf = PassphraseKdf::parse("scrypt");
+    }
+
+    if args.iter().any(|x| x == "--kdf")
+    {
+        let i = args.iter().position(|x| x == "--kdf").unwrap();
+        if i+1 < args.len()
+        {
+            kdf = match PassphraseKdf::parse(&args[i+1])
+            {
+                Some(k) => Some(k),
+                None => { return Err(ArgumentError { why: format!("unsupported kdf {}, expected scrypt or pbkdf2", args[i+1]) }); }
+            };
+            args.remove(i);
+            args.remove(i);
+        }
+        else
+        {
+            return Err(ArgumentError { why: "no argument given to --kdf".to_string() });
+        }
+    }
+
+    Ok(kdf)
+}
+
 const PEM_FILE_REGEX: &str = r"[^\s-]*(.pem)$";
 const LKR_FILE_REGEX: &str = r"[^\s-]*(.lkr)$";
+const PUB_FILE_REGEX: &str = r"[^\s-]*(.pub)$";
 
-pub fn extract_pem(args: &mut Vec<String>) -> Result<String, NoSuchFileError>
+pub fn extract_pem_argument(args: &mut Vec<String>) -> Option<String>
 {
     if args.iter().any(|x| x == "--k")
     {
@@ -42,19 +85,183 @@ pub fn extract_pem(args: &mut Vec<String>) -> Result<String, NoSuchFileError>
             let s = args[i+1].parse::<String>().unwrap();
             args.remove(i);
             args.remove(i);
-            return Ok(s)
+            return Some(s)
         }
     }
+    None
+}
 
+pub fn extract_pem(args: &mut Vec<String>) -> Result<String, NoSuchFileError>
+{
+    match extract_pem_argument(args)
+    {
+        Some(s) => Ok(s),
+        None => find_pem(None)
+    }
+}
+
+/*
+    A public key (.pub) for the owner of the lkr file at lkr_path, 
+        from the keyring or the current directory, e.g. to encrypt 
+        an entry copied into it
+*/
+pub fn find_public(lkr_path: &str) -> Option<String>
+{
+    let mut lkr = Locker::new();
+    let owner = match lkr.read(lkr_path).ok().and_then(|_| lkr.owner())
+    {
+        Some(o) => o,
+        None => { return None; }
+    };
+
+    if let Some(pem) = Keyring::new(&Keyring::default_dir()).find(std::slice::from_ref(&owner))
+    {
+        return Some(public_key_path(&pem));
+    }
+
+    find_files_in_dir(Regex::new(PUB_FILE_REGEX).unwrap()).into_iter().find
+    (
+        |p| build_public(p).is_ok_and(|public| dump_bytes(&fingerprint(&public)).eq_ignore_ascii_case(&owner))
+    )
+}
+
+/*
+    Select a PEM for the lkr file at lkr_path (if given), in order
+
+        a key in the keyring matching the fingerprint recorded in 
+            the lkr file
+        a PEM in the current directory whose .pub matches it
+        the keyring's default key
+        the first PEM in the current directory
+*/
+pub fn find_pem(lkr_path: Option<&str>) -> Result<String, NoSuchFileError>
+{
+    let keyring = Keyring::new(&Keyring::default_dir());
     let re = Regex::new(PEM_FILE_REGEX).unwrap();
-    match find_file_in_dir(re)
+    let local = find_files_in_dir(re.clone());
+
+    let fingerprints = match lkr_path
+    {
+        Some(path) if Path::new(path).exists() => 
+        {
+            let mut lkr = Locker::new();
+            match lkr.read(path)
+            {
+                Ok(_) => 
+                {
+                    let mut fps = lkr.recipients();
+                    fps.extend(lkr.owner());
+                    fps
+                },
+                Err(_) => Vec::new()
+            }
+        },
+        _ => Vec::new()
+    };
+
+    if !fingerprints.is_empty()
+    {
+        if let Some(pem) = keyring.find(&fingerprints)
+        {
+            return Ok(pem)
+        }
+
+        for pem in &local
+        {
+            if let Ok(public) = build_public(&public_key_path(pem))
+            {
+                let fp = dump_bytes(&fingerprint(&public));
+                if fingerprints.iter().any(|f| f.eq_ignore_ascii_case(&fp))
+                {
+                    return Ok(pem.to_string())
+                }
+            }
+        }
+    }
+
+    if let Some(pem) = keyring.default_key()
     {
-        Ok(name) => Ok(name),
-        Err(why) => 
+        return Ok(pem)
+    }
+
+    if local.len() > 1
+    {
+        warning(format!("Multiple PEM files found, using {} (specify with --k)", local[0]).as_str());
+    }
+
+    match local.first()
+    {
+        Some(pem) => Ok(pem.clone()),
+        None => Err(NoSuchFileError{why: format!("No match for pattern: {}", re)})
+    }
+}
+
+pub fn extract_pub(args: &mut Vec<String>) -> Option<String>
+{
+    if args.iter().any(|x| x == "--pub")
+    {
+        let i = args.iter().position(|x| x == "--pub").unwrap();
+        if i+1 < args.len()
         {
-            Err(why)
+            let s = args[i+1].parse::<String>().unwrap();
+            args.remove(i);
+            args.remove(i);
+            return Some(s)
         }
     }
+    None
+}
+
+pub fn extract_group(args: &mut Vec<String>) -> Option<String>
+{
+    if args.iter().any(|x| x == "--group")
+    {
+        let i = args.iter().position(|x| x == "--group").unwrap();
+        if i+1 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            args.remove(i);
+            args.remove(i);
+            return Some(s)
+        }
+    }
+    None
+}
+
+/*
+    Each --field name=value, in order
+*/
+pub fn extract_fields(args: &mut Vec<String>) -> Vec<String>
+{
+    let mut fields: Vec<String> = Vec::new();
+    while let Some(i) = args.iter().position(|x| x == "--field")
+    {
+        args.remove(i);
+        if i < args.len()
+        {
+            fields.push(args.remove(i));
+        }
+    }
+    fields
+}
+
+/*
+    --shares a,b,... shares (or share files) to unlock a split key
+*/
+pub fn extract_shares(args: &mut Vec<String>) -> Option<Vec<String>>
+{
+    if args.iter().any(|x| x == "--shares")
+    {
+        let i = args.iter().position(|x| x == "--shares").unwrap();
+        if i+1 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            args.remove(i);
+            args.remove(i);
+            return Some(s.split(',').map(|x| x.to_string()).collect())
+        }
+    }
+    None
 }
 
 pub fn extract_lkr(args: &mut Vec<String>) -> Option<String>
@@ -71,14 +278,13 @@ pub fn extract_lkr(args: &mut Vec<String>) -> Option<String>
         }
     }
     let re = Regex::new(LKR_FILE_REGEX).unwrap();
-    match find_file_in_dir(re)
-    {
-        Ok(name) => Some(name),
-        Err(_why) => None
-    }
+    find_file_in_dir(re).ok()
 }
 
-pub fn extract_arguments(args: Vec<String>) -> Result<(Option<String>, Option<String>, Option<String>), ArgumentError>
+// the lkr file, entry key and value
+pub type Arguments = (Option<String>, Option<String>, Option<String>);
+
+pub fn extract_arguments(args: Vec<String>) -> Result<Arguments, ArgumentError>
 {
     let mut key: Option<String> = None;
     let mut data: Option<String> = None;
@@ -106,9 +312,9 @@ pub fn extract_arguments(args: Vec<String>) -> Result<(Option<String>, Option<St
         }
     }
 
-    if args_to_parse.len() >= 1
+    if !args_to_parse.is_empty()
     {
-        key = Some(args_to_parse.get(0).unwrap().to_string());
+        key = Some(args_to_parse[0].to_string());
     }
     
     if args_to_parse.len() > 1
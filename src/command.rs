@@ -1,16 +1,20 @@
 use crate::
 {
     error::{CommandError, CommandResult},
-    file::{Locker, EntryPlainText}, crypto::{generate_key, build_rsa}, arguments::extract_pass, util::{write_file, read_file_utf8}
+    file::{Locker, EntryPlainText, KeyOrder, annotated_key_tree, namespace_key, strip_namespace}, age::{self, AgeRecipient, AgeIdentity}, crypto::{generate_key, build_key, build_public, fingerprint, public_key_path, change_passphrase, is_legacy_pem, upgrade_key, seal, open, is_recovery_code, prompt_new_passphrase, KeyOptions, PemCipher}, arguments::{extract_pass, find_pem, find_public}, util::{write_file, read_file_raw, read_file_utf8, dump_bytes, glob_regex},
+    keyring::Keyring,
+    key::{Recipient, KeyAlgorithm},
+    secret::SecretString
 };
 
 use std::path::Path;
+use std::io::Write;
 
-use openssl::
-{
-    rsa::Rsa,
-    pkey::Private
-};
+use regex::Regex;
+
+use openssl::pkey::{PKey, Private, Public};
+
+use base64::{Engine as _, engine::general_purpose};
 
 #[derive(Debug, Clone)]
 pub enum CommandCode
@@ -19,7 +23,71 @@ pub enum CommandCode
     GenKey,
     ReKey,
     Export,
-    Import
+    Import,
+    AddRecipient,
+    RemoveRecipient,
+    ListRecipients,
+    AddGroup,
+    ListGroups,
+    KeyringList,
+    KeyringAdd,
+    KeyringRemove,
+    KeyringDefault,
+    AgeRecipient,
+    Passwd,
+    UpgradeKey,
+    AddRecovery,
+    Recover,
+    Split,
+    OpenShare,
+    GitMerge,
+    GitDiff,
+    Diff,
+    Merge,
+    Move,
+    Copy,
+    List,
+    DeleteNamespace
+}
+
+/*
+    How --merge resolves a key with different values in each locker
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeStrategy
+{
+    PreferA,
+    PreferB,
+    Newest,
+    Interactive
+}
+
+impl MergeStrategy
+{
+    pub fn parse(name: &str) -> Option<MergeStrategy>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "prefer_a" => Some(MergeStrategy::PreferA),
+            "prefer_b" => Some(MergeStrategy::PreferB),
+            "newest" => Some(MergeStrategy::Newest),
+            "interactive" => Some(MergeStrategy::Interactive),
+            _ => None
+        }
+    }
+}
+
+/*
+    How --ls filters and prints keys, the pattern is a glob unless
+        regex is set
+*/
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions
+{
+    pub pattern: Option<String>,
+    pub regex: bool,
+    pub order: KeyOrder,
+    pub tree: bool
 }
 
 #[derive(Debug, Clone)]
@@ -27,201 +95,1935 @@ pub struct Command
 {
     code: CommandCode,
     argument: Option<String>,
-    data: Option<String>
+    data: Option<String>,
+    force: bool,
+    mark_rotation: bool,
+    to: Option<String>,
+    pass: Option<String>,
+    key_options: KeyOptions,
+    identity: Option<String>,
+    age_pass: Option<String>,
+    paths: Vec<String>,
+    to_pub: Option<String>,
+    list: ListOptions,
+    namespace: Option<String>
+}
+
+impl Command
+{
+    pub fn new(code: CommandCode, argument: Option<String>, data: Option<String>) -> Command
+    {
+        Command { code, argument, data, force: false, mark_rotation: false, to: None, pass: None, key_options: KeyOptions::default(), identity: None, age_pass: None, paths: Vec::new(), to_pub: None, list: ListOptions::default(), namespace: None }
+    }
+
+    /*
+        The lkr file a command is given in place of --f, e.g. the 
+            file git passes to --git_diff
+    */
+    pub fn lkr_path(&self) -> Option<String>
+    {
+        match self.code
+        {
+            CommandCode::GitDiff => self.argument.clone(),
+            CommandCode::Diff | CommandCode::Merge => self.paths.first().cloned(),
+            _ => None
+        }
+    }
+
+    /*
+        Whether the command is --recover, which unlocks the locker 
+            with a recovery code in place of a PEM
+    */
+    pub fn recovering(&self) -> bool
+    {
+        matches!(self.code, CommandCode::Recover)
+    }
+
+    /*
+        The recovery code, if given with --recovery_code rather than 
+            at the prompt
+    */
+    pub fn recovery_code(&self) -> Option<String>
+    {
+        match self.code
+        {
+            CommandCode::Recover => self.data.clone(),
+            _ => None
+        }
+    }
 }
 
+/*
+    Extract a command, along with the options shared by commands
+
+        -force     allow existing files to be overwritten
+        --to x     a target, e.g. an existing PEM for --re_key
+        --bits n        size of a generated (RSA) key
+        --cipher c      cipher of a generated PEM
+        --algorithm a   rsa or x25519, for a generated key
+        --identity f    an age identity file, for --import
+        --age_pass p    an age passphrase, for --export and --import
+*/
 pub fn extract_command(args: &mut Vec<String>) -> Result<Option<Command>, CommandError>
+{
+    let force = match args.iter().position(|x| x == "-force")
+    {
+        Some(i) => { args.remove(i); true },
+        None => false
+    };
+
+    let to = match args.iter().position(|x| x == "--to")
+    {
+        Some(i) if i+1 < args.len() => 
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            args.remove(i);
+            args.remove(i);
+            Some(s)
+        },
+        Some(_) => { return Err(CommandError { why: "no argument given to --to".to_string() }); },
+        None => None
+    };
+
+    let mut key_options = KeyOptions::default();
+
+    match args.iter().position(|x| x == "--bits")
+    {
+        Some(i) if i+1 < args.len() => 
+        {
+            key_options.bits = match args[i+1].parse::<u32>()
+            {
+                Ok(b) => b,
+                Err(_) => { return Err(CommandError { why: format!("--bits expects a number, got {}", args[i+1]) }); }
+            };
+            args.remove(i);
+            args.remove(i);
+        },
+        Some(_) => { return Err(CommandError { why: "no argument given to --bits".to_string() }); },
+        None => ()
+    }
+
+    match args.iter().position(|x| x == "--cipher")
+    {
+        Some(i) if i+1 < args.len() => 
+        {
+            key_options.cipher = match PemCipher::parse(&args[i+1])
+            {
+                Some(c) => c,
+                None => { return Err(CommandError { why: format!("unknown cipher {}, expected aes128, aes192 or aes256", args[i+1]) }); }
+            };
+            args.remove(i);
+            args.remove(i);
+        },
+        Some(_) => { return Err(CommandError { why: "no argument given to --cipher".to_string() }); },
+        None => ()
+    }
+
+    match args.iter().position(|x| x == "--algorithm")
+    {
+        Some(i) if i+1 < args.len() => 
+        {
+            key_options.algorithm = match KeyAlgorithm::parse(&args[i+1])
+            {
+                Some(a) => a,
+                None => { return Err(CommandError { why: format!("unknown algorithm {}, expected rsa or x25519", args[i+1]) }); }
+            };
+            args.remove(i);
+            args.remove(i);
+        },
+        Some(_) => { return Err(CommandError { why: "no argument given to --algorithm".to_string() }); },
+        None => ()
+    }
+
+    let identity = extract_option(args, "--identity")?;
+    let age_pass = extract_option(args, "--age_pass")?;
+    let namespace = extract_option(args, "--ns")?;
+
+    match extract_command_code(args)
+    {
+        Ok(Some(command)) => Ok(Some(Command { force, to, key_options, identity, age_pass, namespace, ..command })),
+        other => other
+    }
+}
+
+fn extract_option(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, CommandError>
+{
+    match args.iter().position(|x| x == flag)
+    {
+        Some(i) if i+1 < args.len() => 
+        {
+            args.remove(i);
+            Ok(Some(args.remove(i)))
+        },
+        Some(_) => Err(CommandError { why: format!("no argument given to {}", flag) }),
+        None => Ok(None)
+    }
+}
+
+fn extract_command_code(args: &mut Vec<String>) -> Result<Option<Command>, CommandError>
 {
 
     if args.iter().any(|x| x == "-show_keys")
     {
         let i = args.iter().position(|x| x == "-show_keys").unwrap();
         args.remove(i);
-        return Ok(Some(Command { code: CommandCode::ShowKeys, argument: None, data: None}));
+        return Ok(Some(Command::new(CommandCode::ShowKeys, None, None)));
     }
 
-    if args.iter().any(|x| x == "--export")
+    if args.iter().any(|x| x == "--delete_ns")
     {
-        let i = args.iter().position(|x| x == "--export").unwrap();
+        let i = args.iter().position(|x| x == "--delete_ns").unwrap();
+        args.remove(i);
 
-        if i+1 < args.len()
+        if i >= args.len() || args[i].starts_with("-")
         {
-            let s = args[i+1].parse::<String>().unwrap();
-            if s.find("-").is_none()
-            {
-                args.remove(i);   
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::Export, argument: Some(s), data: None }));
-            }
-            else 
+            return Err(CommandError { why: "no namespace given to --delete_ns".to_string() });
+        }
+
+        return Ok(Some(Command::new(CommandCode::DeleteNamespace, Some(args.remove(i)), None)));
+    }
+
+    if args.iter().any(|x| x == "--ls")
+    {
+        let order = match extract_option(args, "--sort")?
+        {
+            Some(s) => match KeyOrder::parse(&s)
             {
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::Export, argument: None, data: None}));
-            }
+                Some(o) => o,
+                None => { return Err(CommandError { why: format!("unknown sort {}, expected name or modified", s) }); }
+            },
+            None => KeyOrder::Name
+        };
+
+        let mut flag = |name: &str| match args.iter().position(|x| x == name)
+        {
+            Some(j) => { args.remove(j); true },
+            None => false
+        };
+        let regex = flag("-regex");
+        let tree = flag("-tree");
+
+        let i = args.iter().position(|x| x == "--ls").unwrap();
+        args.remove(i);
+
+        let pattern = match i < args.len() && !args[i].starts_with("-")
+        {
+            true => Some(args.remove(i)),
+            false => None
+        };
+
+        let mut command = Command::new(CommandCode::List, None, None);
+        command.list = ListOptions { pattern, regex, order, tree };
+        return Ok(Some(command));
+    }
+
+    if args.iter().any(|x| x == "-age_recipient")
+    {
+        let i = args.iter().position(|x| x == "-age_recipient").unwrap();
+        args.remove(i);
+        return Ok(Some(Command::new(CommandCode::AgeRecipient, None, None)));
+    }
+
+    if args.iter().any(|x| x == "-add_recovery")
+    {
+        let i = args.iter().position(|x| x == "-add_recovery").unwrap();
+        args.remove(i);
+        return Ok(Some(Command::new(CommandCode::AddRecovery, None, None)));
+    }
+
+    if args.iter().any(|x| x == "--recover")
+    {
+        // kept out of argv (and shell history) unless opted in to
+        let code = extract_option(args, "--recovery_code")?;
+
+        let i = args.iter().position(|x| x == "--recover").unwrap();
+        args.remove(i);
+
+        // optional [pem] for the new key
+        let path = match i < args.len() && !args[i].starts_with("-")
+        {
+            true => Some(args.remove(i)),
+            false => None
+        };
+
+        if path.as_deref().is_some_and(is_recovery_code)
+        {
+            return Err(CommandError { why: "the recovery code is asked for, or given with --recovery_code code, not after --recover".to_string() });
         }
-        else
+
+        let mut command = Command::new(CommandCode::Recover, path, code);
+        command.pass = extract_pass(args);
+        return Ok(Some(command));
+    }
+
+    if args.iter().any(|x| x == "-split")
+    {
+        let i = args.iter().position(|x| x == "-split").unwrap();
+        args.remove(i);
+
+        let shares = extract_option(args, "--shares")?;
+        let threshold = extract_option(args, "--threshold")?;
+
+        if shares.is_none() || threshold.is_none()
         {
-            args.remove(i);
-            return Ok(Some(Command { code: CommandCode::Export, argument: None, data: None }));
+            return Err(CommandError { why: "-split requires --shares n and --threshold m".to_string() });
         }
+
+        return Ok(Some(Command::new(CommandCode::Split, shares, threshold)));
     }
 
-    if args.iter().any(|x| x == "--import")
+    if args.iter().any(|x| x == "--open_share")
     {
-        let i = args.iter().position(|x| x == "--import").unwrap();
+        let i = args.iter().position(|x| x == "--open_share").unwrap();
+        args.remove(i);
 
-        if i+1 < args.len()
+        if i >= args.len() || args[i].starts_with("-")
         {
-            let s = args[i+1].parse::<String>().unwrap();
-            if s.find("-").is_none()
+            return Err(CommandError { why: "no share file given to --open_share".to_string() });
+        }
+
+        let share = args.remove(i);
+
+        // optional [pem] the share is encrypted to
+        let pem = match i < args.len() && !args[i].starts_with("-")
+        {
+            true => Some(args.remove(i)),
+            false => None
+        };
+
+        let mut command = Command::new(CommandCode::OpenShare, Some(share), pem);
+        command.pass = extract_pass(args);
+        return Ok(Some(command));
+    }
+
+    if args.iter().any(|x| x == "--git_merge")
+    {
+        let i = args.iter().position(|x| x == "--git_merge").unwrap();
+        args.remove(i);
+
+        if i+3 > args.len()
+        {
+            return Err(CommandError { why: "--git_merge expects the base, ours and theirs lkr files (%O %A %B)".to_string() });
+        }
+
+        let mut command = Command::new(CommandCode::GitMerge, None, None);
+        command.paths = args.drain(i..i+3).collect();
+        return Ok(Some(command));
+    }
+
+    if args.iter().any(|x| x == "--git_diff")
+    {
+        let i = args.iter().position(|x| x == "--git_diff").unwrap();
+        args.remove(i);
+
+        if i >= args.len()
+        {
+            return Err(CommandError { why: "no lkr file given to --git_diff".to_string() });
+        }
+
+        let path = args.remove(i);
+
+        let values = match args.iter().position(|x| x == "-show_values")
+        {
+            Some(j) => { args.remove(j); Some("show_values".to_string()) },
+            None => None
+        };
+
+        return Ok(Some(Command::new(CommandCode::GitDiff, Some(path), values)));
+    }
+
+    for (flag, code) in [("--diff", CommandCode::Diff), ("--merge", CommandCode::Merge)]
+    {
+        if args.iter().any(|x| x == flag)
+        {
+            let strategy = match extract_option(args, "--strategy")?
             {
-                args.remove(i);   
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::Import, argument: Some(s), data: None }));
-            }
-            else 
+                Some(s) if MergeStrategy::parse(&s).is_none() => 
+                {
+                    return Err(CommandError { why: format!("unknown strategy {}, expected prefer_a, prefer_b, newest or interactive", s) });
+                },
+                s => s
+            };
+
+            let values = match args.iter().position(|x| x == "-show_values")
             {
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::Import, argument: None, data: None}));
+                Some(j) => { args.remove(j); Some("show_values".to_string()) },
+                None => None
+            };
+
+            let i = args.iter().position(|x| x == flag).unwrap();
+            args.remove(i);
+
+            if i+2 > args.len()
+            {
+                return Err(CommandError { why: format!("{} expects two lkr files", flag) });
             }
+
+            let mut command = Command::new(code, strategy, values);
+            command.paths = args.drain(i..i+2).collect();
+            return Ok(Some(command));
         }
-        else
+    }
+
+    for (flag, code) in [("--mv", CommandCode::Move), ("--cp", CommandCode::Copy)]
+    {
+        if args.iter().any(|x| x == flag)
         {
+            let to_key = extract_option(args, "--to_key")?;
+            let to_pub = extract_option(args, "--to_pub")?;
+
+            let i = args.iter().position(|x| x == flag).unwrap();
             args.remove(i);
-            return Ok(Some(Command { code: CommandCode::Import, argument: None, data: None }));
+
+            if i >= args.len() || args[i].starts_with("-")
+            {
+                return Err(CommandError { why: format!("no key given to {}", flag) });
+            }
+
+            let key = args.remove(i);
+
+            // the new name, for a rename
+            let name = match to_key
+            {
+                Some(n) => Some(n),
+                None if i < args.len() && !args[i].starts_with("-") => Some(args.remove(i)),
+                None => None
+            };
+
+            let mut command = Command::new(code, Some(key), name);
+            command.to_pub = to_pub;
+            return Ok(Some(command));
         }
     }
 
-    if args.iter().any(|x| x == "--gen_key")
+    if args.iter().any(|x| x == "-list_recipients")
     {
-        let i = args.iter().position(|x| x == "--gen_key").unwrap();
+        let i = args.iter().position(|x| x == "-list_recipients").unwrap();
+        args.remove(i);
+        return Ok(Some(Command::new(CommandCode::ListRecipients, None, None)));
+    }
+
+    if args.iter().any(|x| x == "--add_recipient")
+    {
+        let i = args.iter().position(|x| x == "--add_recipient").unwrap();
 
         if i+1 < args.len()
         {
             let s = args[i+1].parse::<String>().unwrap();
-            if s.find("-").is_none()
-            {
-                args.remove(i);   
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::GenKey, argument: Some(s), data: extract_pass(args)}));
-            }
-            else 
-            {
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::GenKey, argument: None, data: extract_pass(args)}));
-            }
+            args.remove(i);   
+            args.remove(i);
+            return Ok(Some(Command::new(CommandCode::AddRecipient, Some(s), None)));
         }
         else
         {
-            args.remove(i);
-            return Ok(Some(Command { code: CommandCode::GenKey, argument: None, data: extract_pass(args)}));
+            return Err(CommandError { why: "no public key given as argument to --add_recipient".to_string() });
         }
     }
 
-    if args.iter().any(|x| x == "--re_key")
+    if args.iter().any(|x| x == "-keyring_list")
     {
-        let i = args.iter().position(|x| x == "--re_key").unwrap();
+        let i = args.iter().position(|x| x == "-keyring_list").unwrap();
+        args.remove(i);
+        return Ok(Some(Command::new(CommandCode::KeyringList, None, None)));
+    }
+
+    if args.iter().any(|x| x == "--keyring_add")
+    {
+        let i = args.iter().position(|x| x == "--keyring_add").unwrap();
 
         if i+2 < args.len()
         {
             let s = args[i+1].parse::<String>().unwrap();
             let p = args[i+2].parse::<String>().unwrap();
-            
-            if s.find("-").is_none() && p.find("-").is_none()
-            {
-                args.remove(i);   
-                args.remove(i);
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::ReKey, argument: Some(s), data: Some(p) }));
-            }
-            else if s.find("-").is_none()
+            args.remove(i);   
+            args.remove(i);
+            args.remove(i);
+            let mut command = Command::new(CommandCode::KeyringAdd, Some(s), Some(p));
+            command.pass = extract_pass(args);
+            return Ok(Some(command));
+        }
+        else
+        {
+            return Err(CommandError { why: "--keyring_add requires a name and a PEM file".to_string() });
+        }
+    }
+
+    for (flag, code) in [("--keyring_remove", CommandCode::KeyringRemove), ("--keyring_default", CommandCode::KeyringDefault)]
+    {
+        if args.iter().any(|x| x == flag)
+        {
+            let i = args.iter().position(|x| x == flag).unwrap();
+
+            if i+1 < args.len()
             {
+                let s = args[i+1].parse::<String>().unwrap();
                 args.remove(i);   
                 args.remove(i);
-                return Ok(Some(Command { code: CommandCode::ReKey, argument: Some(s), data: None }));
+                return Ok(Some(Command::new(code, Some(s), None)));
             }
-            else 
+            else
             {
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::ReKey, argument: None, data: None }));
+                return Err(CommandError { why: format!("no key name given as argument to {}", flag) });
             }
-            
         }
-        if i+1 < args.len()
+    }
+
+    if args.iter().any(|x| x == "-list_groups")
+    {
+        let i = args.iter().position(|x| x == "-list_groups").unwrap();
+        args.remove(i);
+        return Ok(Some(Command::new(CommandCode::ListGroups, None, None)));
+    }
+
+    if args.iter().any(|x| x == "--add_group")
+    {
+        let i = args.iter().position(|x| x == "--add_group").unwrap();
+
+        if i+2 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            let m = args[i+2].parse::<String>().unwrap();
+            args.remove(i);   
+            args.remove(i);
+            args.remove(i);
+            return Ok(Some(Command::new(CommandCode::AddGroup, Some(s), Some(m))));
+        }
+        else
+        {
+            return Err(CommandError { why: "--add_group requires a name and comma separated fingerprints".to_string() });
+        }
+    }
+
+    if args.iter().any(|x| x == "--remove_recipient")
+    {
+        let i = args.iter().position(|x| x == "--remove_recipient").unwrap();
+
+        if i+1 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            args.remove(i);   
+            args.remove(i);
+
+            let mut command = Command::new(CommandCode::RemoveRecipient, Some(s), None);
+            command.mark_rotation = match args.iter().position(|x| x == "-mark_rotation")
+            {
+                Some(j) => { args.remove(j); true },
+                None => false
+            };
+
+            return Ok(Some(command));
+        }
+        else
+        {
+            return Err(CommandError { why: "no fingerprint given as argument to --remove_recipient".to_string() });
+        }
+    }
+
+    if args.iter().any(|x| x == "--export")
+    {
+        let i = args.iter().position(|x| x == "--export").unwrap();
+
+        if i+1 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            if s.find("-").is_none()
+            {
+                args.remove(i);   
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::Export, Some(s), None)));
+            }
+            else 
+            {
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::Export, None, None)));
+            }
+        }
+        else
+        {
+            args.remove(i);
+            return Ok(Some(Command::new(CommandCode::Export, None, None)));
+        }
+    }
+
+    if args.iter().any(|x| x == "--import")
+    {
+        let i = args.iter().position(|x| x == "--import").unwrap();
+
+        if i+1 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            if s.find("-").is_none()
+            {
+                args.remove(i);   
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::Import, Some(s), None)));
+            }
+            else 
+            {
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::Import, None, None)));
+            }
+        }
+        else
+        {
+            args.remove(i);
+            return Ok(Some(Command::new(CommandCode::Import, None, None)));
+        }
+    }
+
+    if args.iter().any(|x| x == "--gen_key")
+    {
+        let i = args.iter().position(|x| x == "--gen_key").unwrap();
+
+        if i+1 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            if s.find("-").is_none()
+            {
+                args.remove(i);   
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::GenKey, Some(s), extract_pass(args))));
+            }
+            else 
+            {
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::GenKey, None, extract_pass(args))));
+            }
+        }
+        else
+        {
+            args.remove(i);
+            return Ok(Some(Command::new(CommandCode::GenKey, None, extract_pass(args))));
+        }
+    }
+
+    if args.iter().any(|x| x == "--passwd")
+    {
+        let i = args.iter().position(|x| x == "--passwd").unwrap();
+        args.remove(i);
+
+        let path = match i < args.len() && !args[i].starts_with("-")
+        {
+            true => Some(args.remove(i)),
+            false => None
+        };
+
+        // argv ends up in shell history and ps
+        if i < args.len() && !args[i].starts_with("-")
+        {
+            return Err(CommandError { why: "the new passphrase is asked for, not given after --passwd".to_string() });
+        }
+
+        let mut command = Command::new(CommandCode::Passwd, path, None);
+        command.pass = extract_pass(args);
+        return Ok(Some(command));
+    }
+
+    if args.iter().any(|x| x == "--upgrade_key")
+    {
+        let i = args.iter().position(|x| x == "--upgrade_key").unwrap();
+        args.remove(i);
+
+        let path = match i < args.len() && !args[i].starts_with("-")
+        {
+            true => Some(args.remove(i)),
+            false => None
+        };
+
+        let mut command = Command::new(CommandCode::UpgradeKey, path, None);
+        command.pass = extract_pass(args);
+        return Ok(Some(command));
+    }
+
+    if args.iter().any(|x| x == "--re_key")
+    {
+        let i = args.iter().position(|x| x == "--re_key").unwrap();
+
+        if i+2 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            let p = args[i+2].parse::<String>().unwrap();
+            
+            if s.find("-").is_none() && p.find("-").is_none()
+            {
+                args.remove(i);   
+                args.remove(i);
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::ReKey, Some(s), Some(p))));
+            }
+            else if s.find("-").is_none()
+            {
+                args.remove(i);   
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::ReKey, Some(s), None)));
+            }
+            else 
+            {
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::ReKey, None, None)));
+            }
+            
+        }
+        if i+1 < args.len()
+        {
+            let s = args[i+1].parse::<String>().unwrap();
+            if s.find("-").is_none()
+            {
+                args.remove(i);   
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::ReKey, Some(s), None)));
+            }
+            else 
+            {
+                args.remove(i);
+                return Ok(Some(Command::new(CommandCode::ReKey, None, None)));
+            }
+        }
+        else
+        {
+            args.remove(i);
+            return Ok(Some(Command::new(CommandCode::ReKey, None, None)));
+        }
+    }
+
+    Ok(None)
+}
+
+pub fn handle_free_command(command: Command) -> Result<CommandResult, CommandError>
+{
+    match command.code 
+    {
+        CommandCode::GenKey => 
+        {
+            gen_key(command.argument, command.data.map(SecretString::new), command.force, &command.key_options)
+        },
+        CommandCode::KeyringList =>
+        {
+            keyring_list()
+        },
+        CommandCode::Passwd =>
+        {
+            passwd(command.argument, command.pass)
+        },
+        CommandCode::UpgradeKey =>
+        {
+            upgrade(command.argument, command.pass)
+        },
+        CommandCode::OpenShare =>
+        {
+            open_share(command.argument.unwrap(), command.data, command.pass)
+        },
+        CommandCode::GitMerge =>
+        {
+            git_merge(&command.paths[0], &command.paths[1], &command.paths[2])
+        },
+        CommandCode::KeyringAdd =>
+        {
+            keyring_add(command.argument.unwrap(), command.data.unwrap(), command.pass)
+        },
+        CommandCode::KeyringRemove =>
+        {
+            match Keyring::new(&Keyring::default_dir()).remove(&command.argument.unwrap())
+            {
+                Ok(_) => Ok(CommandResult::OK),
+                Err(e) => Err(CommandError { why: format!("{}", e) })
+            }
+        },
+        CommandCode::KeyringDefault =>
+        {
+            match Keyring::new(&Keyring::default_dir()).set_default(&command.argument.unwrap())
+            {
+                Ok(_) => Ok(CommandResult::OK),
+                Err(e) => Err(CommandError { why: format!("{}", e) })
+            }
+        },
+        _ => {Ok(CommandResult::NothingToDo)}
+    }
+}
+
+pub fn handle_command(lkr_path: &str, identity: PKey<Private>, command: Command) -> Result<CommandResult, CommandError>
+{
+    match command.code
+    {
+        CommandCode::ShowKeys => 
+        {
+            show_keys(lkr_path, identity)
+        },
+        CommandCode::List =>
+        {
+            list(lkr_path, identity, &command.list, command.namespace)
+        },
+        CommandCode::DeleteNamespace =>
+        {
+            delete_namespace(lkr_path, identity, &command.argument.unwrap(), command.force)
+        },
+        CommandCode::ReKey =>
+        {
+            re_key(lkr_path, identity, command.argument, command.data, command.to, command.force, &command.key_options)
+        },
+        CommandCode::Export =>
+        {
+            export(lkr_path, identity, command.argument, command.to, command.age_pass, command.namespace)
+        },
+        CommandCode::Import =>
+        {
+            import(lkr_path, identity, command.argument, command.identity, command.age_pass, command.namespace)
+        },
+        CommandCode::AgeRecipient =>
+        {
+            match age::recipient_string(&identity.public())
+            {
+                Ok(r) => { println!("{}", r); Ok(CommandResult::OK) },
+                Err(e) => Err(CommandError { why: format!("{}", e) })
+            }
+        },
+        CommandCode::AddRecipient =>
+        {
+            add_recipient(lkr_path, identity, command.argument)
+        },
+        CommandCode::RemoveRecipient =>
+        {
+            remove_recipient(lkr_path, identity, command.argument.unwrap(), command.mark_rotation)
+        },
+        CommandCode::ListRecipients =>
+        {
+            list_recipients(lkr_path, identity)
+        },
+        CommandCode::AddRecovery =>
+        {
+            add_recovery(lkr_path, identity)
+        },
+        CommandCode::Split =>
+        {
+            split(lkr_path, identity, command.argument.unwrap(), command.data.unwrap(), command.to)
+        },
+        CommandCode::Recover =>
+        {
+            recover(lkr_path, identity, command.argument, command.pass, command.to, command.force, &command.key_options)
+        },
+        CommandCode::AddGroup =>
+        {
+            add_group(lkr_path, command.argument.unwrap(), command.data.unwrap())
+        },
+        CommandCode::ListGroups =>
+        {
+            list_groups(lkr_path)
+        },
+        CommandCode::GitDiff =>
+        {
+            git_diff(lkr_path, identity, command.data.is_some())
+        },
+        CommandCode::Diff =>
+        {
+            diff(&command.paths[0], &command.paths[1], identity, command.data.is_some())
+        },
+        CommandCode::Move | CommandCode::Copy =>
+        {
+            let keep = matches!(command.code, CommandCode::Copy);
+            move_entry(lkr_path, identity, command.argument.unwrap(), command.data, command.to, command.to_pub, keep)
+        },
+        CommandCode::Merge =>
+        {
+            merge(&command.paths[0], &command.paths[1], identity, command.argument.and_then(|s| MergeStrategy::parse(&s)), command.to, command.data.is_some())
+        }
+        _ => {Ok(CommandResult::NothingToDo)}
+    }
+}
+
+/*
+    Export readable entries as json, encrypted with age if 
+        recipients (age1..., comma separated) or a passphrase 
+        are given. Entries are exported from namespace, if given,
+        with their names relative to it
+*/
+fn export(lkr_path: &str, identity: PKey<Private>, path: Option<String>, to: Option<String>, age_pass: Option<String>, namespace: Option<String>) -> Result<CommandResult, CommandError>
+{
+    if !Path::new(lkr_path).exists()
+    {
+        return Err(CommandError { why: format!("show_keys, lkr file {} does not exist", lkr_path) });
+    }
+
+    let mut lkr = Locker::new();
+
+    match lkr.read(lkr_path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    let mut plaintext: Vec<EntryPlainText> = Vec::new();
+
+    let namespace = namespace.unwrap_or_default();
+
+    for key in lkr.namespace_keys(&identity, &namespace)
+    {
+        let name = strip_namespace(&key, &namespace).unwrap().to_string();
+        let fields = match lkr.is_structured(&key)
+        {
+            true => match lkr.get_fields(&key, &identity)
+            {
+                Ok(f) => Some(f),
+                Err(e) => { return Err(CommandError { why: format!("Could not export {}: {}", key, e) }); }
+            },
+            false => None
+        };
+        let value = match lkr.get(&key, &identity)
+        {
+            Ok(v) => v,
+            Err(e) => { return Err(CommandError { why: format!("Could not export {}: {}", key, e) }); }
+        };
+        plaintext.push(EntryPlainText{ key: name, value: value.to_string(), fields });
+    }
+
+    let export_path = match path 
+    {
+        Some(p) => p,
+        None => { "exported".to_string() }
+    };
+
+    let se = match serde_json::to_string_pretty(&plaintext)
+    {
+        Ok(se) => se,
+        Err(why) => 
+        {
+            return Err(CommandError { why: format!("serde_json serialisation error: {}", why)})
+        }
+    };
+
+    let mut recipients: Vec<AgeRecipient> = Vec::new();
+
+    for r in to.iter().flat_map(|t| t.split(','))
+    {
+        match age::parse_recipient(r)
+        {
+            Ok(public) => recipients.push(AgeRecipient::X25519(public)),
+            Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+        }
+    }
+
+    if let Some(pass) = age_pass
+    {
+        recipients.push(AgeRecipient::Scrypt(pass, age::SCRYPT_WORK_FACTOR));
+    }
+
+    match recipients.is_empty()
+    {
+        true => write_file(&export_path, se.as_bytes()),
+        false => match age::encrypt(&recipients, se.as_bytes())
+        {
+            Ok(data) => write_file(&export_path, &data),
+            Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+        }
+    }
+
+    Ok(CommandResult::OK)
+    
+}
+
+/*
+    Import json entries, decrypting an age file with the identities
+        in identity_file, the locker's own (X25519) key, or a passphrase.
+        Entries are imported into namespace, if given
+*/
+fn import(lkr_path: &str, identity: PKey<Private>, path: Option<String>, identity_file: Option<String>, age_pass: Option<String>, namespace: Option<String>) -> Result<CommandResult, CommandError>
+{
+    let in_file = match path 
+    {
+        Some(f) => f,
+        None => 
+        {
+            return Err(CommandError { why: "no import path given as argument to --import".to_string() });
+        }
+    };
+
+    let mut raw = match read_file_raw(&in_file)
+    {
+        Ok(d) => d, 
+        Err(e) => 
+        {
+            return Err(CommandError { why: format!("Could not read import file {}: {}", in_file, e) });
+        }
+    };
+
+    if age::is_age(&raw)
+    {
+        let mut identities: Vec<AgeIdentity> = Vec::new();
+
+        if let Some(file) = identity_file
+        {
+            match age::read_identities(&file)
+            {
+                Ok(ids) => identities.extend(ids.into_iter().map(AgeIdentity::X25519)),
+                Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+            }
+        }
+
+        if KeyAlgorithm::of(&identity) == Some(KeyAlgorithm::X25519)
+        {
+            identities.push(AgeIdentity::X25519(identity.clone()));
+        }
+
+        if let Some(pass) = age_pass
+        {
+            identities.push(AgeIdentity::Scrypt(pass));
+        }
+
+        raw = match age::decrypt(&identities, &raw)
+        {
+            Ok(d) => d,
+            Err(e) => { return Err(CommandError { why: format!("Could not decrypt import file {}: {}", in_file, e) }); }
+        };
+    }
+
+    let data_string = match String::from_utf8(raw)
+    {
+        Ok(d) => d,
+        Err(_) => { return Err(CommandError { why: format!("Import file {} is not UTF-8", in_file) }); }
+    };
+
+    let data: Vec<EntryPlainText> = match serde_json::from_str(&data_string)
+    {
+        Ok(d) => d,
+        Err(e) => {return Err(CommandError { why: format!("Could not parse import file {}: {}", in_file, e) });}
+    };
+
+
+    let mut lkr = match Path::new(lkr_path).exists()
+    {
+        true => read_locker(lkr_path)?,
+        false => Locker::new()
+    };
+
+    let namespace = namespace.unwrap_or_default();
+
+    for entry in data 
+    {
+        let key = namespace_key(&namespace, &entry.key);
+        let inserted = match entry.fields
+        {
+            Some(fields) => lkr.insert_structured(&key, &fields, &identity, false).map_err(|e| format!("{}", e)),
+            None => lkr.insert(&key, &entry.value, &identity, false).map_err(|e| format!("{}", e))
+        };
+
+        match inserted
+        {
+            Ok(_) => {},
+            Err(why) => {return Err(CommandError { why: format!("Could not import {}: {}", key, why) });}
+        }
+    }
+
+    match lkr.write(lkr_path)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(e) => Err(CommandError { why:format!("{}", e) })
+    }
+
+}
+
+// entries re-keyed between writes of the lkr file
+const REKEY_WRITE_EVERY: usize = 64;
+
+/*
+    Rotate the locker to a new key (written to path), or to an 
+        existing key (to), one entry at a time. The rotation is 
+        recorded in the lkr file so an interrupted re_key resumes 
+        when run again
+*/
+fn re_key(lkr_path: &str, old: PKey<Private>, path: Option<String>, pass: Option<String>, to: Option<String>, force: bool, key_options: &KeyOptions) -> Result<CommandResult, CommandError>
+{
+    let mut lkr = Locker::new();
+
+    if !Path::new(lkr_path).exists()
+    {
+        return Err(CommandError { why: format!("Locker file {}, does not exit", lkr_path) });
+    }
+    
+    match lkr.read(lkr_path)
+    {
+        Ok(_) => (),
+        Err(why) => 
+        {
+            return Err(CommandError { why: format!("{}", why) });
+        }
+    }
+
+    let new = match to
+    {
+        Some(existing) => 
+        {
+            if path.is_some()
+            {
+                return Err(CommandError { why: "re_key takes either a new pem, or an existing one with --to".to_string() });
+            }
+            match existing_public_key(&existing)
+            {
+                Ok(r) => r,
+                Err(e) => { return Err(e); }
+            }
+        },
+        None => 
+        {
+            let pem = match path
+            {
+                Some(p) => p,
+                None => "locker.pem".to_string()
+            };
+
+            let resuming = match (lkr.rotation(), build_public(&public_key_path(&pem)))
+            {
+                (Some(r), Ok(p)) => r == dump_bytes(&fingerprint(&p)),
+                _ => false
+            };
+
+            if !resuming
+            {
+                match gen_key(Some(pem.clone()), pass.map(SecretString::new), force, key_options)
+                {
+                    Ok(_r) => (),
+                    Err(e) => return Err(e)
+                }
+            }
+
+            match build_public(&public_key_path(&pem))
+            {
+                Ok(v) => v,
+                Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+            }
+        }
+    };
+
+    if lkr.owner() == Some(dump_bytes(&fingerprint(&new)))
+    {
+        println!("locker is already encrypted to {}", dump_bytes(&fingerprint(&new)));
+        return Ok(CommandResult::OK)
+    }
+
+    match lkr.start_rotation(&new)
+    {
+        Ok(_) => (),
+        Err(why) => { return Err(CommandError { why: format!("{}", why) }); }
+    }
+
+    // the rotation is recorded before any entry is re-encrypted
+    match lkr.write(lkr_path)
+    {
+        Ok(_) => (),
+        Err(why) => { return Err(CommandError { why: format!("{}", why) }); }
+    }
+
+    let mut pending = 0;
+
+    loop
+    {
+        match lkr.rekey_next(&old, &new)
+        {
+            Ok(Some((done, total))) => { println!("re-keyed {}/{}", done, total); },
+            Ok(None) => { break; },
+            Err(why) => { return Err(CommandError { why: format!("{}", why) }); }
+        }
+
+        pending += 1;
+
+        if pending == REKEY_WRITE_EVERY
+        {
+            pending = 0;
+            match lkr.write_no_backup(lkr_path)
+            {
+                Ok(_) => (),
+                Err(why) => { return Err(CommandError { why: format!("{}", why) }); }
+            }
+        }
+    }
+
+    // the .bk is left as the locker was before the rotation
+    match lkr.write_no_backup(lkr_path)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(why) => { Err(CommandError { why: format!("{}", why) }) }
+    }
+}
+
+/*
+    The public key of an existing PEM, from its .pub if present
+        otherwise by decrypting the PEM
+*/
+fn existing_public_key(pem: &str) -> Result<PKey<Public>, CommandError>
+{
+    if !Path::new(pem).exists()
+    {
+        return Err(CommandError { why: format!("PEM file {} does not exist", pem) });
+    }
+
+    match build_public(&public_key_path(pem))
+    {
+        Ok(p) => Ok(p),
+        Err(_) => 
+        {
+            let password = SecretString::new(rpassword::prompt_password
+            (
+                format!("Passphrase for PEM file {}: ", pem)
+            ).unwrap());
+
+            match build_key(pem, &password)
+            {
+                Ok(key) => Ok(key.public()),
+                Err(e) => Err(CommandError { why: format!("{}", e) })
+            }
+        }
+    }
+}
+
+fn gen_key(path: Option<String>, pass: Option<SecretString>, force: bool, key_options: &KeyOptions) -> Result<CommandResult, CommandError>
+{
+    let result = match path
+    {
+        Some(p) => generate_key(&p, pass, force, key_options),
+        None => generate_key("locker.pem", pass, force, key_options)
+    };
+
+    match result
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(e) => Err(CommandError { why: format!("While generating key: {}", e) })
+    }
+}
+
+fn passwd(path: Option<String>, old: Option<String>) -> Result<CommandResult, CommandError>
+{
+    let pem = match path
+    {
+        Some(p) => p,
+        None => match find_pem(None)
+        {
+            Ok(p) => p,
+            Err(e) => { return Err(CommandError { why: format!("Could not find PEM: {}", e) }); }
+        }
+    };
+
+    let old = SecretString::new(match old 
+    {
+        Some(s) => s,
+        None => 
+        {
+            rpassword::prompt_password
+            (
+                format!("Current passphrase for PEM file {}: ", pem)
+            ).unwrap()
+        }
+    });
+
+    // check the current passphrase before asking for a new one
+    match build_key(&pem, &old)
+    {
+        Ok(_) => (),
+        Err(e) => { return Err(CommandError { why: e.why }); }
+    }
+
+    let new = match prompt_new_passphrase(&format!("New passphrase for PEM file {}: ", pem))
+    {
+        Some(p) => p,
+        None => { return Err(CommandError { why: format!("Passphrases do not match, {} is unchanged", pem) }); }
+    };
+
+    match change_passphrase(&pem, &old, &new)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(e) => Err(CommandError { why: format!("{}", e) })
+    }
+}
+
+fn upgrade(path: Option<String>, pass: Option<String>) -> Result<CommandResult, CommandError>
+{
+    let pem = match path
+    {
+        Some(p) => p,
+        None => match find_pem(None)
+        {
+            Ok(p) => p,
+            Err(e) => { return Err(CommandError { why: format!("Could not find PEM: {}", e) }); }
+        }
+    };
+
+    if !is_legacy_pem(&pem)
+    {
+        println!("{} is already PKCS#8", pem);
+        return Ok(CommandResult::OK);
+    }
+
+    let pass = SecretString::new(match pass 
+    {
+        Some(s) => s,
+        None => 
+        {
+            rpassword::prompt_password
+            (
+                format!("Passphrase for PEM file {}: ", pem)
+            ).unwrap()
+        }
+    });
+
+    match upgrade_key(&pem, &pass)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(e) => Err(CommandError { why: format!("{}", e) })
+    }
+}
+
+fn keyring_list() -> Result<CommandResult, CommandError>
+{
+    for entry in Keyring::new(&Keyring::default_dir()).list()
+    {
+        match entry.default
+        {
+            true => println!("{} {} (default)", entry.name, entry.fingerprint),
+            false => println!("{} {}", entry.name, entry.fingerprint)
+        }
+    }
+
+    Ok(CommandResult::OK)
+}
+
+/*
+    Add pem to the keyring under name. The public key is derived 
+        from the PEM, not read from a .pub beside it, which may not
+        match
+*/
+fn keyring_add(name: String, pem: String, pass: Option<String>) -> Result<CommandResult, CommandError>
+{
+    if !Path::new(&pem).exists()
+    {
+        return Err(CommandError { why: format!("PEM file {} does not exist", pem) });
+    }
+
+    let password = SecretString::new(match pass
+    {
+        Some(p) => p,
+        None => rpassword::prompt_password(format!("Passphrase for PEM file {}: ", pem)).unwrap()
+    });
+
+    let public = match build_key(&pem, &password)
+    {
+        Ok(key) => key.public(),
+        Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+    };
+
+    match Keyring::new(&Keyring::default_dir()).add(&name, &pem, public)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(e) => Err(CommandError { why: format!("{}", e) })
+    }
+}
+
+/*
+    Print the keys matching options' pattern, in order or as a tree.
+        Values are not decrypted
+*/
+fn list(lkr_path: &str, identity: PKey<Private>, options: &ListOptions, namespace: Option<String>) -> Result<CommandResult, CommandError>
+{
+    let lkr = read_locker(lkr_path)?;
+
+    let pattern = match &options.pattern
+    {
+        Some(p) =>
+        {
+            let re = match options.regex
+            {
+                true => p.clone(),
+                false => glob_regex(p)
+            };
+
+            match Regex::new(&re)
+            {
+                Ok(re) => Some(re),
+                Err(e) => { return Err(CommandError { why: format!("bad pattern {}: {}", p, e) }); }
+            }
+        },
+        None => None
+    };
+
+    let namespace = namespace.unwrap_or_default();
+
+    let keys: Vec<String> = lkr.list_keys(&identity, pattern.as_ref(), options.order).into_iter().filter(|k| strip_namespace(k, &namespace).is_some()).collect();
+
+    // annotated after filtering, so the note is not part of the key
+    let note = |key: &str| match lkr.needs_rotation(key)
+    {
+        true => " (needs rotation)".to_string(),
+        false => String::new()
+    };
+
+    let lines = match options.tree
+    {
+        true => annotated_key_tree(&keys, note),
+        false => keys.iter().map(|k| format!("{}{}", k, note(k))).collect()
+    };
+
+    for line in lines
+    {
+        println!("{}", line);
+    }
+
+    Ok(CommandResult::OK)
+}
+
+/*
+    Delete every key in namespace, after listing them and asking for
+        confirmation unless force
+*/
+fn delete_namespace(lkr_path: &str, identity: PKey<Private>, namespace: &str, force: bool) -> Result<CommandResult, CommandError>
+{
+    let mut lkr = read_locker(lkr_path)?;
+    let keys = lkr.namespace_keys(&identity, namespace);
+
+    if keys.is_empty()
+    {
+        return Err(CommandError { why: format!("no keys in namespace {}", namespace) });
+    }
+
+    if !force
+    {
+        for key in &keys
+        {
+            println!("{}", key);
+        }
+        print!("delete these {} keys? [y/N] ", keys.len());
+        std::io::stdout().flush().unwrap();
+
+        let mut answer = String::new();
+        match std::io::stdin().read_line(&mut answer)
+        {
+            Ok(_) if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") => {},
+            Ok(_) => { return Ok(CommandResult::NothingToDo); },
+            Err(e) => { return Err(CommandError { why: format!("reading answer: {}", e) }); }
+        }
+    }
+
+    match lkr.delete_namespace(namespace, &identity)
+    {
+        Ok(_) => {},
+        Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+    }
+
+    match lkr.write(lkr_path)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(why) => Err(CommandError { why: format!("{}", why) })
+    }
+}
+
+fn show_keys(lkr_path: &str, identity: PKey<Private>) -> Result<CommandResult, CommandError>
+{
+
+    if !Path::new(lkr_path).exists()
+    {
+        return Err(CommandError { why: format!("show_keys, lkr file {} does not exist", lkr_path) });
+    }
+
+    let mut lkr = Locker::new();
+
+    match lkr.read(lkr_path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+    
+    let keys = lkr.get_keys(&identity);
+
+    for key in keys 
+    {
+        match lkr.needs_rotation(&key)
+        {
+            true => println!("{} (needs rotation)", key),
+            false => println!("{}", key)
+        }
+    }
+
+    let hidden = lkr.hidden_keys(identity);
+
+    if hidden > 0
+    {
+        println!("({} keys not readable with this key)", hidden);
+    }
+
+    Ok(CommandResult::OK)
+}
+
+fn add_recipient(lkr_path: &str, identity: PKey<Private>, path: Option<String>) -> Result<CommandResult, CommandError>
+{
+    let public = match path 
+    {
+        Some(p) => p,
+        None => 
+        {
+            return Err(CommandError { why: "no public key given as argument to --add_recipient".to_string() });
+        }
+    };
+
+    let recipient = match build_public(&public)
+    {
+        Ok(r) => r,
+        Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+    };
+
+    if !Path::new(lkr_path).exists()
+    {
+        return Err(CommandError { why: format!("add_recipient, lkr file {} does not exist", lkr_path) });
+    }
+
+    let mut lkr = Locker::new();
+
+    match lkr.read(lkr_path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    match lkr.add_recipient(recipient, identity)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    match lkr.write(lkr_path)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(why) => { Err(CommandError { why: format!("{}", why) }) }
+    }
+}
+
+fn remove_recipient(lkr_path: &str, identity: PKey<Private>, fingerprint: String, mark_rotation: bool) -> Result<CommandResult, CommandError>
+{
+    if !Path::new(lkr_path).exists()
+    {
+        return Err(CommandError { why: format!("remove_recipient, lkr file {} does not exist", lkr_path) });
+    }
+
+    let mut lkr = Locker::new();
+
+    match lkr.read(lkr_path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    match lkr.remove_recipient(&fingerprint, identity, mark_rotation)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    match lkr.write(lkr_path)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(why) => { Err(CommandError { why: format!("{}", why) }) }
+    }
+}
+
+fn add_group(lkr_path: &str, name: String, members: String) -> Result<CommandResult, CommandError>
+{
+    if !Path::new(lkr_path).exists()
+    {
+        return Err(CommandError { why: format!("add_group, lkr file {} does not exist", lkr_path) });
+    }
+
+    let mut lkr = Locker::new();
+
+    match lkr.read(lkr_path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    match lkr.add_group(&name, members.split(",").map(|m| m.to_string()).collect())
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    match lkr.write(lkr_path)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(why) => { Err(CommandError { why: format!("{}", why) }) }
+    }
+}
+
+/*
+    A git merge driver, a three-way merge of base, ours and theirs 
+        (%O %A %B) written to ours, see Locker::merge
+*/
+fn git_merge(base: &str, ours: &str, theirs: &str) -> Result<CommandResult, CommandError>
+{
+    let read = |path: &str| -> Result<Locker, CommandError>
+    {
+        let mut lkr = Locker::new();
+
+        // a file added on both sides has an empty base
+        if read_file_raw(path).is_ok_and(|d| d.is_empty())
+        {
+            return Ok(lkr);
+        }
+
+        match lkr.read(path)
+        {
+            Ok(_) => Ok(lkr),
+            Err(why) => Err(CommandError { why: format!("{}", why) })
+        }
+    };
+
+    let merged = match Locker::merge(&read(base)?, &read(ours)?, &read(theirs)?)
+    {
+        Ok(lkr) => lkr,
+        Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+    };
+
+    match merged.write_no_backup(ours)
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(why) => Err(CommandError { why: format!("{}", why) })
+    }
+}
+
+/*
+    A git textconv, the keys of a locker and digests of their 
+        values (or the values) as text, see Locker::describe
+*/
+fn git_diff(lkr_path: &str, identity: PKey<Private>, values: bool) -> Result<CommandResult, CommandError>
+{
+    let mut lkr = Locker::new();
+
+    match lkr.read(lkr_path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    for line in lkr.describe(&identity, values)
+    {
+        println!("{}", line);
+    }
+
+    Ok(CommandResult::OK)
+}
+
+fn read_locker(path: &str) -> Result<Locker, CommandError>
+{
+    let mut lkr = Locker::new();
+
+    match lkr.read(path)
+    {
+        Ok(_) => Ok(lkr),
+        Err(why) => Err(CommandError { why: format!("{}", why) })
+    }
+}
+
+/*
+    Print keys only in a, only in b and those with different values
+*/
+fn diff(a_path: &str, b_path: &str, identity: PKey<Private>, values: bool) -> Result<CommandResult, CommandError>
+{
+    let (a, b) = (read_locker(a_path)?, read_locker(b_path)?);
+    let diff = a.diff(&b, &identity);
+
+    for (title, keys) in [(format!("only in {}", a_path), &diff.only_a), (format!("only in {}", b_path), &diff.only_b)]
+    {
+        if !keys.is_empty()
+        {
+            println!("{}", title);
+            for key in keys
+            {
+                println!("  {}", key);
+            }
+        }
+    }
+
+    if !diff.different.is_empty()
+    {
+        println!("different");
+        for key in &diff.different
+        {
+            println!("  {}", key);
+            if values
+            {
+                for (path, lkr) in [(a_path, &a), (b_path, &b)]
+                {
+                    match lkr.get(key, &identity)
+                    {
+                        Ok(v) => println!("    {}: {}", path, v.as_str()),
+                        Err(e) => println!("    {}: {}", path, e)
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(CommandResult::OK)
+}
+
+/*
+    Ask which of a's or b's value of key to keep
+*/
+fn choose(key: &str, a: (&str, &Locker), b: (&str, &Locker), identity: &PKey<Private>, values: bool) -> Result<bool, CommandError>
+{
+    println!("{} differs", key);
+    for (label, (path, lkr)) in [("a", a), ("b", b)]
+    {
+        let modified = match lkr.modified(key)
+        {
+            Some(t) => format!(", modified {}", t),
+            None => String::new()
+        };
+
+        match (values, lkr.get(key, identity))
+        {
+            (true, Ok(v)) => println!("  [{}] {}{}: {}", label, path, modified, v.as_str()),
+            _ => println!("  [{}] {}{}", label, path, modified)
+        }
+    }
+
+    loop
+    {
+        print!("keep a or b? ");
+        std::io::stdout().flush().unwrap();
+
+        let mut answer = String::new();
+        match std::io::stdin().read_line(&mut answer)
+        {
+            Ok(0) => { return Err(CommandError { why: "no answer given, nothing merged".to_string() }); },
+            Ok(_) => {},
+            Err(e) => { return Err(CommandError { why: format!("reading answer: {}", e) }); }
+        }
+
+        match answer.trim()
+        {
+            "a" => { return Ok(false); },
+            "b" => { return Ok(true); },
+            _ => {}
+        }
+    }
+}
+
+/*
+    Merge b into a (or a copy of a at to), keys only in b are added
+        and keys with different values are resolved by strategy,
+        keys deleted from either are not detected
+*/
+fn merge(a_path: &str, b_path: &str, identity: PKey<Private>, strategy: Option<MergeStrategy>, to: Option<String>, values: bool) -> Result<CommandResult, CommandError>
+{
+    let (mut a, b) = (read_locker(a_path)?, read_locker(b_path)?);
+    let diff = a.diff(&b, &identity);
+
+    // every conflict is resolved before anything is changed
+    let mut from_b = diff.only_b.clone();
+
+    for key in &diff.different
+    {
+        let prefer_b = match strategy
         {
-            let s = args[i+1].parse::<String>().unwrap();
-            if s.find("-").is_none()
+            None => 
             {
-                args.remove(i);   
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::ReKey, argument: Some(s), data: None }));
-            }
-            else 
+                return Err(CommandError { why: format!("{} keys differ (e.g. {}), choose a --strategy", diff.different.len(), key) });
+            },
+            Some(MergeStrategy::PreferA) => false,
+            Some(MergeStrategy::PreferB) => true,
+            Some(MergeStrategy::Newest) => match (a.modified(key), b.modified(key))
             {
-                args.remove(i);
-                return Ok(Some(Command { code: CommandCode::ReKey, argument: None, data: None }));
-            }
-        }
-        else
+                (Some(ta), Some(tb)) => tb > ta,
+                _ => 
+                {
+                    return Err(CommandError { why: format!("{} has no modified time in both lkr files, choose another --strategy", key) });
+                }
+            },
+            Some(MergeStrategy::Interactive) => choose(key, (a_path, &a), (b_path, &b), &identity, values)?
+        };
+
+        if prefer_b { from_b.push(key.clone()); }
+    }
+
+    for key in &from_b
+    {
+        match a.copy_entry(&b, key, key, &identity, &identity, true)
         {
-            args.remove(i);
-            return Ok(Some(Command { code: CommandCode::ReKey, argument: None, data: None }));
+            Ok(_) => {},
+            Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
         }
     }
 
-    Ok(None)
+    let out = match to
+    {
+        Some(p) => p,
+        None => a_path.to_string()
+    };
+
+    match a.write(&out)
+    {
+        Ok(_) => 
+        {
+            println!("{} keys added, {} of {} different values taken from {}", diff.only_b.len(), from_b.len()-diff.only_b.len(), diff.different.len(), b_path);
+            Ok(CommandResult::OK)
+        },
+        Err(why) => Err(CommandError { why: format!("{}", why) })
+    }
 }
 
-pub fn handle_free_command(command: Command) -> Result<CommandResult, CommandError>
+/*
+    The key to encrypt an entry copied into dest with, to_pub if 
+        given, otherwise identity if dest is new, sealed to its 
+        recipients or owned by identity, otherwise dest's owner as
+        found by find_public
+*/
+fn destination_key(dest: &Locker, dest_path: &str, identity: &PKey<Private>, to_pub: Option<String>) -> Result<PKey<Public>, CommandError>
 {
-    match command.code 
+    let foreign_owner = dest.owner().is_some_and(|o| o != dump_bytes(&fingerprint(identity))) && dest.recipients().is_empty();
+
+    let path = match (to_pub, foreign_owner)
     {
-        CommandCode::GenKey => 
+        (Some(p), _) => p,
+        (None, false) => { return Ok(identity.public()); },
+        (None, true) => match find_public(dest_path)
         {
-            match command.data
+            Some(p) => p,
+            None => 
             {
-                Some(data) => { gen_key(command.argument, Some(data.clone())) },
-                None => { gen_key(command.argument, None) }
+                return Err(CommandError { why: format!("no public key found for the owner of {}, give it with --to_pub", dest_path) });
             }
-        },
-        _ => {Ok(CommandResult::NothingToDo)}
+        }
+    };
+
+    let public = match build_public(&path)
+    {
+        Ok(p) => p,
+        Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+    };
+
+    if dest.recipients().is_empty() && dest.owner().is_some_and(|o| o != dump_bytes(&fingerprint(&public)))
+    {
+        return Err(CommandError { why: format!("{} is not the key {} is encrypted to", path, dest_path) });
     }
+
+    Ok(public)
 }
 
-pub fn handle_command(lkr_path: &str, rsa: Rsa<Private>, command: Command) -> Result<CommandResult, CommandError>
+/*
+    Rename key to name, or move (copy if keep) it to the locker at 
+        to, as name if given. The value is decrypted in memory only,
+        both lockers are written only once both are ready
+*/
+/*
+    Whether a and b name the same file, e.g. a.lkr and ./a.lkr. A 
+        file that does not exist is not the same as any other
+*/
+fn same_file(a: &str, b: &str) -> bool
 {
-    match command.code
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b))
     {
-        CommandCode::ShowKeys => 
-        {
-            show_keys(lkr_path, rsa)
-        },
-        CommandCode::ReKey =>
-        {
-            re_key(lkr_path, rsa, command.argument, command.data)
-        },
-        CommandCode::Export =>
+        (Ok(a), Ok(b)) => a == b,
+        _ => Path::new(a) == Path::new(b)
+    }
+}
+
+fn move_entry(lkr_path: &str, identity: PKey<Private>, key: String, name: Option<String>, to: Option<String>, to_pub: Option<String>, keep: bool) -> Result<CommandResult, CommandError>
+{
+    let mut source = read_locker(lkr_path)?;
+
+    let to = match to
+    {
+        Some(t) if !same_file(&t, lkr_path) => t,
+        _ => 
         {
-            export(lkr_path, rsa, command.argument)
-        },
-        CommandCode::Import =>
+            return match (name, keep)
+            {
+                (Some(name), false) => match source.rename(&key, &name, &identity)
+                {
+                    Ok(_) => match source.write(lkr_path)
+                    {
+                        Ok(_) => Ok(CommandResult::OK),
+                        Err(why) => Err(CommandError { why: format!("{}", why) })
+                    },
+                    Err(e) => Err(CommandError { why: format!("{}", e) })
+                },
+                (None, false) => Err(CommandError { why: "--mv needs a new name, or another lkr file with --to".to_string() }),
+                (_, true) => Err(CommandError { why: "--cp needs another lkr file with --to".to_string() })
+            };
+        }
+    };
+
+    let mut dest = match Path::new(&to).exists()
+    {
+        true => read_locker(&to)?,
+        false => Locker::new()
+    };
+
+    let recipient = destination_key(&dest, &to, &identity, to_pub)?;
+    let name = match name
+    {
+        Some(n) => n,
+        None => key.clone()
+    };
+
+    match dest.copy_entry(&source, &key, &name, &identity, &recipient, false)
+    {
+        Ok(_) => {},
+        Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+    }
+
+    let written = match keep
+    {
+        true => dest.write(&to),
+        false => 
         {
-            import(lkr_path, rsa, command.argument)
+            match source.delete(&key, &identity)
+            {
+                Ok(_) => {},
+                Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+            }
+            Locker::write_all(&[(&dest, to.as_str()), (&source, lkr_path)])
         }
-        _ => {Ok(CommandResult::NothingToDo)}
+    };
+
+    match written
+    {
+        Ok(_) => Ok(CommandResult::OK),
+        Err(why) => Err(CommandError { why: format!("{}", why) })
     }
 }
 
-fn export(lkr_path: &str, rsa: Rsa<Private>, path: Option<String>) -> Result<CommandResult, CommandError>
+fn list_groups(lkr_path: &str) -> Result<CommandResult, CommandError>
 {
     if !Path::new(lkr_path).exists()
     {
-        return Err(CommandError { why: format!("show_keys, lkr file {} does not exist", lkr_path) });
+        return Err(CommandError { why: format!("list_groups, lkr file {} does not exist", lkr_path) });
     }
 
     let mut lkr = Locker::new();
 
-    match lkr.read(&lkr_path)
+    match lkr.read(lkr_path)
     {
         Ok(_) => {},
         Err(why) => 
@@ -230,177 +2032,319 @@ fn export(lkr_path: &str, rsa: Rsa<Private>, path: Option<String>) -> Result<Com
         }
     }
 
-    let mut plaintext: Vec<EntryPlainText> = Vec::new();
+    for (name, members) in lkr.groups()
+    {
+        println!("{}", name);
+        for member in members
+        {
+            println!("  {}", member);
+        }
+    }
+
+    Ok(CommandResult::OK)
+}
 
-    for key in lkr.get_keys(rsa.clone())
+/*
+    Escrow a recovery code, printed once
+*/
+fn add_recovery(lkr_path: &str, identity: PKey<Private>) -> Result<CommandResult, CommandError>
+{
+    if !Path::new(lkr_path).exists()
     {
-        plaintext.push(EntryPlainText{ key: key.clone(), value: lkr.get(&key, rsa.clone()).unwrap()});
+        return Err(CommandError { why: format!("add_recovery, lkr file {} does not exist", lkr_path) });
     }
 
-    let export_path = match path 
+    let mut lkr = Locker::new();
+
+    match lkr.read(lkr_path)
     {
-        Some(p) => p,
-        None => { "exported".to_string() }
-    };
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
 
-    match serde_json::to_string_pretty(&plaintext)
+    let code = match lkr.add_recovery(identity)
     {
-        Ok(se) => 
+        Ok(c) => c,
+        Err(why) => 
         {
-            write_file(&export_path, se.as_bytes())
-        },
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    };
+
+    match lkr.write(lkr_path)
+    {
+        Ok(_) => {},
         Err(why) => 
         {
-            return Err(CommandError { why: format!("serde_json serialisation error: {}", why)})
+            return Err(CommandError{why: format!("{}", why)})
         }
     }
 
+    println!("Recovery code for {}, store it offline, it will not be shown again:\n\n    {}\n", lkr_path, code);
+    println!("Recover the locker to a new key with --recover [pem], which asks for the code");
+
     Ok(CommandResult::OK)
-    
 }
 
-fn import(lkr_path: &str, rsa: Rsa<Private>, path: Option<String>) -> Result<CommandResult, CommandError>
+/*
+    Recover the locker to a new key (written to path), or to an 
+        existing key (to), with the recovery key as identity
+*/
+fn recover(lkr_path: &str, recovery: PKey<Private>, path: Option<String>, pass: Option<String>, to: Option<String>, force: bool, key_options: &KeyOptions) -> Result<CommandResult, CommandError>
 {
-    let in_file = match path 
+    let mut lkr = Locker::new();
+
+    match lkr.read(lkr_path)
     {
-        Some(f) => f,
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
+
+    let new = match to
+    {
+        Some(existing) => 
+        {
+            if path.is_some()
+            {
+                return Err(CommandError { why: "recover takes either a new pem, or an existing one with --to".to_string() });
+            }
+            existing_public_key(&existing)?
+        },
         None => 
         {
-            return Err(CommandError { why: format!("no import path given as argument to --import") });
+            let pem = match path
+            {
+                Some(p) => p,
+                None => "locker.pem".to_string()
+            };
+
+            gen_key(Some(pem.clone()), pass.map(SecretString::new), force, key_options)?;
+
+            match build_public(&public_key_path(&pem))
+            {
+                Ok(v) => v,
+                Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+            }
         }
     };
 
-    let data_string = match read_file_utf8(&in_file)
+    match lkr.recover(&recovery, &new)
     {
-        Ok(d) => d, 
-        Err(e) => 
+        Ok(_) => {},
+        Err(why) => 
         {
-            return Err(CommandError { why: format!("Could not read import file {}: {}", in_file, e) });
+            return Err(CommandError{why: format!("{}", why)})
         }
-    };
+    }
 
-    let data: Vec<EntryPlainText> = match serde_json::from_str(&data_string)
+    match lkr.write(lkr_path)
     {
-        Ok(d) => d,
-        Err(e) => {return Err(CommandError { why: format!("Could not parse import file {}: {}", in_file, e) });}
-    };
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    }
 
+    println!("{} recovered to {}, the recovery code is spent (add another with -add_recovery)", lkr_path, dump_bytes(&fingerprint(&new)));
 
-    let mut lkr = Locker::new();
+    Ok(CommandResult::OK)
+}
 
-    match Path::new(lkr_path).exists()
+const ENCRYPTED_SHARE_HEADER: &str = "LOCKER-ENCRYPTED-SHARE";
+
+/*
+    Split a new key into shares written alongside the lkr file as
+        lkr.share1, lkr.share2, ... either as text or each encrypted
+        to one of the public keys in to (comma separated)
+*/
+fn split(lkr_path: &str, identity: PKey<Private>, shares: String, threshold: String, to: Option<String>) -> Result<CommandResult, CommandError>
+{
+    let (shares, threshold) = match (shares.parse::<u8>(), threshold.parse::<u8>())
     {
-        true => {lkr.read(lkr_path).unwrap();},
-        false => ()
-    }
+        (Ok(s), Ok(t)) => (s, t),
+        _ => { return Err(CommandError { why: format!("--shares {} and --threshold {} must be numbers below 256", shares, threshold) }); }
+    };
 
-    for entry in data 
+    let mut holders: Vec<PKey<Public>> = Vec::new();
+
+    for pub_path in to.iter().flat_map(|t| t.split(','))
     {
-        lkr.insert(&entry.key, &entry.value, rsa.clone(), false).unwrap();
+        match build_public(pub_path)
+        {
+            Ok(p) => holders.push(p),
+            Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+        }
     }
 
-    match lkr.write(lkr_path)
+    if !holders.is_empty() && holders.len() != shares as usize
     {
-        Ok(_) => Ok(CommandResult::OK),
-        Err(e) => {return Err(CommandError { why:format!("{}", e) });}
+        return Err(CommandError { why: format!("{} public keys given with --to for {} shares", holders.len(), shares) });
     }
 
-}
-
-fn re_key(lkr_path: &str, old_rsa: Rsa<Private>, path: Option<String>, pass: Option<String>) -> Result<CommandResult, CommandError>
-{
-
-    match gen_key(path.clone(), pass.clone())
+    if !Path::new(lkr_path).exists()
     {
-        Ok(_r) => (),
-        Err(e) => return Err(e)
+        return Err(CommandError { why: format!("split, lkr file {} does not exist", lkr_path) });
     }
 
-    let mut old_lkr = Locker::new();
     let mut lkr = Locker::new();
 
-    if !Path::new(lkr_path).exists()
+    match lkr.read(lkr_path)
     {
-        return Err(CommandError { why: format!("Locker file {}, does not exit", lkr_path) });
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
+        }
     }
-    
-    match old_lkr.read(lkr_path)
+
+    let parts = match lkr.split(identity, shares, threshold)
     {
-        Ok(_) => (),
+        Ok(p) => p,
         Err(why) => 
         {
-            return Err(CommandError { why: format!("{}", why) });
+            return Err(CommandError{why: format!("{}", why)})
+        }
+    };
+
+    // the shares are only of use once the locker holds their key
+    match lkr.write(lkr_path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            return Err(CommandError{why: format!("{}", why)})
         }
     }
 
-    let pem = match path
+    for (i, share) in parts.iter().enumerate()
+    {
+        let path = format!("{}.share{}", lkr_path, i+1);
+
+        match holders.get(i)
+        {
+            Some(holder) => 
+            {
+                let sealed = general_purpose::STANDARD.encode(seal(std::slice::from_ref(holder), share.as_bytes()));
+                write_file(&path, format!("{}\n{}\n", ENCRYPTED_SHARE_HEADER, sealed).as_bytes());
+                println!("{} (encrypted to {})", path, dump_bytes(&fingerprint(holder)));
+            },
+            None => 
+            {
+                write_file(&path, format!("{}\n", share).as_bytes());
+                println!("{}", path);
+            }
+        }
+    }
+
+    println!("Any {} of these {} shares unlock {} with --shares", threshold, shares, lkr_path);
+    println!("{}.bk still opens with the old key, delete it once the shares are safe", lkr_path);
+
+    Ok(CommandResult::OK)
+}
+
+/*
+    Print the text of a share encrypted to the key in pem
+*/
+fn open_share(path: String, pem: Option<String>, pass: Option<String>) -> Result<CommandResult, CommandError>
+{
+    let data = match read_file_utf8(&path)
+    {
+        Ok(d) => d,
+        Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+    };
+
+    let sealed = match data.strip_prefix(ENCRYPTED_SHARE_HEADER).and_then(|s| general_purpose::STANDARD.decode(s.trim()).ok())
+    {
+        Some(s) => s,
+        None => { return Err(CommandError { why: format!("{} is not an encrypted share", path) }); }
+    };
+
+    let pem = match pem
     {
         Some(p) => p,
-        None => "locker.pem".to_string()
+        None => match find_pem(None)
+        {
+            Ok(p) => p,
+            Err(e) => { return Err(CommandError { why: format!("Could not find PEM: {}", e) }); }
+        }
     };
 
-    let password = match pass 
+    let pass = SecretString::new(match pass 
     {
         Some(s) => s,
         None => 
         {
             rpassword::prompt_password
             (
-                format!("Re enter passphrase for new key: ")
+                format!("Passphrase for PEM file {}: ", pem)
             ).unwrap()
         }
-    };
+    });
 
-    let rsa = match build_rsa(pem.as_str(), password.as_str())
+    let identity = match build_key(&pem, &pass)
     {
-        Ok(v) => v,
-        Err(e) => 
-        {
-            println!("{}", e.why);
-            std::process::exit(1);
-        }
+        Ok(k) => k,
+        Err(e) => { return Err(CommandError { why: e.why }); }
     };
 
-
-    for key in old_lkr.get_keys(old_rsa.clone())
-    {
-        let value = old_lkr.get(&key, old_rsa.clone()).unwrap();
-        lkr.insert(&key, &value, rsa.clone(), true).unwrap();
-    }
-
-    match lkr.write(lkr_path)
+    match open(&identity, &sealed).and_then(|s| String::from_utf8(s).ok()).map(SecretString::new)
     {
-        Ok(_) => Ok(CommandResult::OK),
-        Err(why) => { Err(CommandError { why: format!("{}", why) }) }
+        Some(share) => 
+        {
+            println!("{}", share.as_str());
+            Ok(CommandResult::OK)
+        },
+        None => Err(CommandError { why: format!("{} is not encrypted to {}", path, pem) })
     }
 }
 
-fn gen_key(path: Option<String>, pass: Option<String>) -> Result<CommandResult, CommandError>
+/*
+    Read shares given as text, or as files written by split
+*/
+pub fn read_shares(shares: &[String]) -> Result<Vec<String>, CommandError>
 {
-    let result = match path
-    {
-        Some(p) => generate_key(&p, pass),
-        None => generate_key("locker.pem", pass)
-    };
+    let mut out: Vec<String> = Vec::new();
 
-    match result
+    for share in shares
     {
-        Ok(_) => Ok(CommandResult::OK),
-        Err(e) => Err(CommandError { why: format!("While generating key: {}", e) })
+        let text = match Path::new(share).exists()
+        {
+            true => match read_file_utf8(share)
+            {
+                Ok(t) => t,
+                Err(e) => { return Err(CommandError { why: format!("{}", e) }); }
+            },
+            false => share.clone()
+        };
+
+        if text.starts_with(ENCRYPTED_SHARE_HEADER)
+        {
+            return Err(CommandError { why: format!("{} is encrypted, decrypt it with --open_share {}", share, share) });
+        }
+
+        out.push(text.trim().to_string());
     }
+
+    Ok(out)
 }
 
-fn show_keys(lkr_path: &str, rsa: Rsa<Private>) -> Result<CommandResult, CommandError>
+fn list_recipients(lkr_path: &str, identity: PKey<Private>) -> Result<CommandResult, CommandError>
 {
-
     if !Path::new(lkr_path).exists()
     {
-        return Err(CommandError { why: format!("show_keys, lkr file {} does not exist", lkr_path) });
+        return Err(CommandError { why: format!("list_recipients, lkr file {} does not exist", lkr_path) });
     }
 
     let mut lkr = Locker::new();
 
-    match lkr.read(&lkr_path)
+    match lkr.read(lkr_path)
     {
         Ok(_) => {},
         Err(why) => 
@@ -408,12 +2352,16 @@ fn show_keys(lkr_path: &str, rsa: Rsa<Private>) -> Result<CommandResult, Command
             return Err(CommandError{why: format!("{}", why)})
         }
     }
-    
-    let keys = lkr.get_keys(rsa);
 
-    for key in keys 
+    let own = dump_bytes(&fingerprint(&identity));
+
+    for recipient in lkr.recipients()
     {
-        println!("{}", key);
+        match recipient == own
+        {
+            true => println!("{} (this key)", recipient),
+            false => println!("{}", recipient)
+        }
     }
 
     Ok(CommandResult::OK)
@@ -9,11 +9,24 @@ use locker::
         decrypt,
         build_rsa
     }, 
+    age::{self, AgeRecipient, AgeIdentity},
+    secret::{SecretString, disable_core_dumps},
     util
 };
 
+fn argument(args: &[String], flag: &str) -> Option<String>
+{
+    match args.iter().position(|x| x == flag)
+    {
+        Some(i) if i+1 < args.len() => Some(args[i+1].clone()),
+        _ => None
+    }
+}
+
 fn main()
 {
+    disable_core_dumps();
+
     let args: Vec<String> = std::env::args().collect();
 
     let pem = if args.iter().any(|x| x == "-k")
@@ -53,13 +66,52 @@ fn main()
         false
     };
 
+    let age_recipient = argument(&args, "-r");
+    let age_identity = argument(&args, "-i");
+
+    if let (true, Some(identity_file)) = (decrypting, &age_identity)
+    {
+        let identities = age::read_identities(identity_file).unwrap();
+        let data = util::read_file_raw(encrypted_file.as_str()).unwrap();
+        let identities: Vec<AgeIdentity> = identities.into_iter().map(AgeIdentity::X25519).collect();
+        match age::decrypt(&identities, &data)
+        {
+            Err(e) => panic!("{}", e),
+            Ok(result) => match std::str::from_utf8(&result)
+            {
+                Err(_e) => {println!("Not UTF8, dumping bytes\n"); for c in result { print!("{} ", c)}},
+                Ok(str) => println!("Decypted data:  \n{}", str)
+            }
+        }
+        return;
+    }
+
+    if let (false, Some(recipient)) = (decrypting, &age_recipient)
+    {
+        let recipient = age::parse_recipient(recipient).unwrap();
+        println!("Enter some data to encrypt:");
+        let mut input = String::new();
+
+        if let Err(why) = io::stdin().read_line(&mut input)
+        {
+            panic!("reading input: {}", why);
+        }
+
+        match age::encrypt(&[AgeRecipient::X25519(recipient)], input.as_bytes())
+        {
+            Err(e) => panic!("{}", e),
+            Ok(result) => util::write_file("out", &result)
+        }
+        return;
+    }
+
     println!("Passphrase for PEM file {}",pem);
-    let mut pass = String::new();
+    // room enough that read_line does not reallocate, leaving a copy
+    let mut pass = String::with_capacity(1024);
     
-    match io::stdin().read_line(&mut pass)
+    if let Err(why) = io::stdin().read_line(&mut pass)
     {
-        Err(why) => panic!("reading input: {}", why),
-        Ok(_) => ()
+        panic!("reading input: {}", why);
     }
 
     if pass.len() > 1
@@ -71,6 +123,8 @@ fn main()
         panic!("passphrase is empty");
     }
 
+    let pass = SecretString::new(pass);
+
     let rsa = build_rsa(pem.as_str(), pass.as_str()).unwrap();
 
     if decrypting
@@ -89,10 +143,9 @@ fn main()
         println!("Enter some data to encrypt:");
         let mut input = String::new();
         
-        match io::stdin().read_line(&mut input)
+        if let Err(why) = io::stdin().read_line(&mut input)
         {
-            Err(why) => panic!("reading input: {}", why),
-            Ok(_) => ()
+            panic!("reading input: {}", why);
         }
     
         let result = encrypt(rsa, input.as_bytes());
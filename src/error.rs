@@ -126,4 +126,124 @@ impl fmt::Display for CompressionError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.why)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone)]
+pub struct RecipientError
+{
+    pub why: String
+}
+
+impl fmt::Display for RecipientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.why)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyMismatchError
+{
+    pub expected: String,
+    pub supplied: String
+}
+
+impl fmt::Display for KeyMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "this locker belongs to key {}, you supplied {}", self.expected, self.supplied)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyringError
+{
+    pub why: String
+}
+
+impl fmt::Display for KeyringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "While using keyring: {}", self.why)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RotationError
+{
+    pub why: String
+}
+
+impl fmt::Display for RotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "While rotating key: {}", self.why)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AgeError
+{
+    pub why: String
+}
+
+impl fmt::Display for AgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "While processing age file: {}", self.why)
+    }
+}
+#[derive(Debug, Clone)]
+pub struct KdfError
+{
+    pub why: String
+}
+
+impl fmt::Display for KdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "While deriving key from passphrase: {}", self.why)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShamirError
+{
+    pub why: String
+}
+
+impl fmt::Display for ShamirError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "While processing shares: {}", self.why)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeError
+{
+    pub why: String
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "While merging lkr files: {}", self.why)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MoveError
+{
+    pub why: String
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "While moving entry: {}", self.why)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldError
+{
+    pub why: String
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "While setting fields: {}", self.why)
+    }
+}
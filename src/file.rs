@@ -4,7 +4,32 @@
         entries. Each entry contains a hash (of the key)
         and an encrypted string (the value)
 
-    All keys (hashes) are enforced to be unique
+    All keys (hashes) are enforced to be unique, entries are written
+        sorted by hash so that an unchanged entry stays in place 
+        (and a locker kept in git diffs cleanly)
+
+    The fingerprint (SHA-256 of the public key) of the key a 
+        locker is encrypted to is recorded as its owner, so that
+        the wrong key is detected before decryption, along with
+        the key's algorithm and size
+
+    A passphrase locker has no PEM, its (X25519) key is derived from 
+        a passphrase with the kdf (and salt) in its header
+
+    A recovery code is escrowed in the same way, its derived key is a
+        recipient so the code can recover the locker to a new key
+
+    A locker's split key is a recipient whose private key is only
+        held as Shamir shares (see shamir), so that a threshold of 
+        share holders must come together to unlock it
+
+    A locker may list recipients (RSA or X25519 public keys), in 
+        which case keys and values are sealed in envelopes readable
+        by each recipient's private key (see crypto::seal)
+
+    Keys may be named in namespaces, prod/db/password is password in
+        namespace prod/db (and prod). Namespaces are only a naming
+        convention, keys are stored as before
 
     [
         {
@@ -24,30 +49,29 @@
 
 use crate::
 {
-    crypto::{hash, encrypt, decrypt_string},
-    util::{write_file, read_file_utf8, dump_bytes, read_bytes, warning, as_base64, from_base64}, 
+    crypto::{hash, encrypt_to, try_decrypt_string, fingerprint, seal, strip_recipient, recovery_code, normalise_recovery_code, PassphraseKdf, SCRYPT_LOG_N, MAX_RECIPIENTS},
+    key::{Recipient, Identity, KeyAlgorithm},
+    util::{try_write_file, restrict_permissions, read_file_utf8, dump_bytes, read_bytes, warning, as_base64, from_base64, as_base64_option, from_base64_option}, 
+    secret::{SecretString, SecretJson, secret_json, wipe},
     program_version,
     compatible,
-    error::{KeyCollisionError, KeyNonExistantError, ReadError, WriteError}, version_compression_added, VERSION_REGEX
+    error::{KeyCollisionError, KeyNonExistantError, ReadError, WriteError, RecipientError, KeyMismatchError, RotationError, KdfError, ShamirError, MergeError, MoveError, FieldError},
+    shamir::{self, share_string, parse_share}, version_compression_added, VERSION_REGEX
 };
 
 use regex::Regex;
 
 use semver::Version;
 
-use openssl::sha::Sha256;
+use openssl::sha::{Sha256, sha256};
 
 use serde::{Deserialize, Serialize};
 
-use std::{collections::HashMap, path::Path};
+use std::{collections::{BTreeMap, BTreeSet}, path::Path};
 
 use std::convert::{From, Into};
 
-use openssl::
-{
-    rsa::Rsa,
-    pkey::Private
-};
+use openssl::{pkey::{PKey, Id, Private, Public}, rand::rand_bytes};
 
 #[derive(Serialize, Deserialize)]
 pub struct Entry0_2_0 
@@ -65,13 +89,52 @@ pub struct Lkr0_2_0
     keys: Vec<String>
 }
 
+/*
+    Plain text information about an entry, empty metadata
+        is not written
+
+    modified is the time (unix seconds) the value was last 
+        inserted, entries written before it was recorded have none
+
+    structured marks a value holding a json object of named fields
+*/
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct Metadata
+{
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub needs_rotation: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<u64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub structured: bool
+}
+
+fn now() -> u64
+{
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl Metadata
+{
+    pub fn is_empty(&self) -> bool
+    {
+        *self == Metadata::default()
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Entry 
 {
     #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
     hash: Vec<u8>,
     #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
-    value: Vec<u8>
+    value: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    metadata: Metadata
 }
 
 impl From<Entry0_2_0> for Entry 
@@ -81,127 +144,1720 @@ impl From<Entry0_2_0> for Entry
         Entry 
         { 
             hash: read_bytes(uncompressed.hash), 
-            value: uncompressed.value.as_bytes().to_vec()
+            value: uncompressed.value.as_bytes().to_vec(),
+            metadata: Metadata::default()
+        }
+    }
+}
+
+impl From<Entry> for Entry0_2_0
+{
+    fn from(entry: Entry) -> Entry0_2_0
+    {
+        Entry0_2_0
+        { 
+            hash: dump_bytes(&entry.hash), 
+            value: dump_bytes(&entry.value)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Key 
+{
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    bytes: Vec<u8>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RecipientKey
+{
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    fingerprint: Vec<u8>,
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    public_key: Vec<u8>
+}
+
+/*
+    A named subset of recipients, members are fingerprints
+*/
+#[derive(Serialize, Deserialize)]
+pub struct Group
+{
+    name: String,
+    members: Vec<Key>
+}
+
+/*
+    Algorithm and size of the owner's key, so that weak keys are
+        reported without the key itself
+*/
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct KeyInfo
+{
+    pub algorithm: String,
+    pub bits: u32
+}
+
+impl KeyInfo
+{
+    pub fn of<K: Recipient>(key: &K) -> KeyInfo
+    {
+        let public = key.public();
+        let algorithm = match KeyAlgorithm::of(&public)
+        {
+            Some(a) => a.name().to_string(),
+            None => "unknown".to_string()
+        };
+        KeyInfo { algorithm, bits: public.bits() }
+    }
+}
+
+/*
+    The fingerprint of a key split into shares, and how many of 
+        them unlock it
+*/
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SplitKey
+{
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub fingerprint: Vec<u8>,
+    pub shares: u8,
+    pub threshold: u8
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Lkr
+{
+    version: String,
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    check_hash: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none", serialize_with = "as_base64_option", deserialize_with = "from_base64_option")]
+    owner: Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", serialize_with = "as_base64_option", deserialize_with = "from_base64_option")]
+    rotating_to: Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner_key: Option<KeyInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kdf: Option<PassphraseKdf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recovery: Option<PassphraseKdf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    split: Option<SplitKey>,
+    entries: Vec<Entry>,
+    keys: Vec<Key>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    recipients: Vec<RecipientKey>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<Group>
+}
+
+pub struct Locker {
+    data: BTreeMap<[u8; 32], Vec<u8>>,
+    keys: Vec<Vec<u8>>,
+    recipients: Vec<PKey<Public>>,
+    groups: BTreeMap<String, Vec<[u8; 32]>>,
+    metadata: BTreeMap<[u8; 32], Metadata>,
+    owner: Option<[u8; 32]>,
+    owner_key: Option<KeyInfo>,
+    kdf: Option<PassphraseKdf>,
+    recovery: Option<PassphraseKdf>,
+    split: Option<SplitKey>,
+    rotating_to: Option<[u8; 32]>,
+    // where rekey_next resumes, not written
+    rekey_cursor: usize
+}
+
+/*
+    Keys only in a, only in b, and in both with different values
+*/
+#[derive(Debug, Default, PartialEq)]
+pub struct LockerDiff
+{
+    pub only_a: Vec<String>,
+    pub only_b: Vec<String>,
+    pub different: Vec<String>
+}
+
+/*
+    The order keys are listed in, modified lists the most recently
+        inserted first and keys with no recorded time last
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KeyOrder
+{
+    #[default]
+    Name,
+    Modified
+}
+
+impl KeyOrder
+{
+    pub fn parse(name: &str) -> Option<KeyOrder>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "name" => Some(KeyOrder::Name),
+            "modified" => Some(KeyOrder::Modified),
+            _ => None
+        }
+    }
+}
+
+struct KeyTreeNode
+{
+    name: String,
+    key: bool,
+    note: String,
+    children: Vec<KeyTreeNode>
+}
+
+impl KeyTreeNode
+{
+    fn insert(&mut self, path: &[&str], note: String)
+    {
+        match path.split_first()
+        {
+            None => { self.key = true; self.note = note; },
+            Some((first, rest)) =>
+            {
+                let i = match self.children.iter().position(|c| c.name == *first)
+                {
+                    Some(i) => i,
+                    None => 
+                    {
+                        self.children.push(KeyTreeNode { name: first.to_string(), key: false, note: String::new(), children: Vec::new() });
+                        self.children.len()-1
+                    }
+                };
+                self.children[i].insert(rest, note);
+            }
+        }
+    }
+
+    fn lines(&self, depth: usize, out: &mut Vec<String>)
+    {
+        let indent = "  ".repeat(depth);
+        if self.key
+        {
+            out.push(format!("{}{}{}", indent, self.name, self.note));
+        }
+        if !self.children.is_empty()
+        {
+            out.push(format!("{}{}/", indent, self.name));
+            for child in &self.children
+            {
+                child.lines(depth+1, out);
+            }
+        }
+    }
+}
+
+/*
+    Keys drawn as a tree, / or . in a key separating folders, 
+        which are written with a trailing / and their contents
+        indented below. Keys keep their order within each folder
+
+    e.g. db.user, db/password and api becomes
+
+    db/
+      user
+      password
+    api
+*/
+pub fn key_tree(keys: &[String]) -> Vec<String>
+{
+    annotated_key_tree(keys, |_| String::new())
+}
+
+/*
+    As key_tree, with note(key) written after each key, e.g. 
+        " (needs rotation)"
+*/
+pub fn annotated_key_tree<F: Fn(&str) -> String>(keys: &[String], note: F) -> Vec<String>
+{
+    let mut root = KeyTreeNode { name: String::new(), key: false, note: String::new(), children: Vec::new() };
+    for key in keys
+    {
+        let path: Vec<&str> = key.split(['/', '.']).collect();
+        root.insert(&path, note(key));
+    }
+
+    let mut out: Vec<String> = Vec::new();
+    for child in &root.children
+    {
+        child.lines(0, &mut out);
+    }
+    out
+}
+
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/*
+    key within namespace, e.g. prod/db and password give 
+        prod/db/password. An empty namespace is the root
+*/
+pub fn namespace_key(namespace: &str, key: &str) -> String
+{
+    match namespace.trim_end_matches(NAMESPACE_SEPARATOR)
+    {
+        "" => key.to_string(),
+        ns => format!("{}{}{}", ns, NAMESPACE_SEPARATOR, key)
+    }
+}
+
+/*
+    The name of key relative to namespace, if it is in it, e.g.
+        prod/db/password in prod is db/password
+*/
+pub fn strip_namespace<'a>(key: &'a str, namespace: &str) -> Option<&'a str>
+{
+    match namespace.trim_end_matches(NAMESPACE_SEPARATOR)
+    {
+        "" => Some(key),
+        ns => key.strip_prefix(ns).and_then(|k| k.strip_prefix(NAMESPACE_SEPARATOR)).filter(|k| !k.is_empty())
+    }
+}
+
+pub const FIELD_SEPARATOR: char = '.';
+
+/*
+    The value at a path of fields (e.g. conn.host) in a json object
+*/
+fn field_at<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value>
+{
+    path.split(FIELD_SEPARATOR).try_fold(value, |v, field| v.as_object().and_then(|o| o.get(field)))
+}
+
+/*
+    An exported entry, a structured entry's fields are also given 
+        as json so that they are nested in the export (its value
+        holds them as a string, for older versions)
+*/
+#[derive(Serialize, Deserialize)]
+pub struct EntryPlainText
+{
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<serde_json::Value>
+} 
+
+// the hash of each entry, and its value if it could be decrypted
+type PlainData = Vec<([u8; 32], Option<SecretString>)>;
+
+impl Default for Locker
+{
+    fn default() -> Locker
+    {
+        Locker::new()
+    }
+}
+
+impl Locker 
+{
+
+    pub fn new() -> Locker
+    {
+        Locker { data: BTreeMap::new(), keys: Vec::new(), recipients: Vec::new(), groups: BTreeMap::new(), metadata: BTreeMap::new(), owner: None, owner_key: None, kdf: None, recovery: None, split: None, rotating_to: None, rekey_cursor: 0 }
+    }
+
+    /*
+        The recipients who may read an entry in group, every 
+            recipient if group is None
+    */
+    fn audience(&self, group: &Option<String>) -> Result<Vec<PKey<Public>>, RecipientError>
+    {
+        match group
+        {
+            None => Ok(self.recipients.clone()),
+            Some(g) => 
+            {
+                let members = self.groups.get(g).ok_or(RecipientError { why: format!("no group {} in lkr file", g) })?;
+                Ok(self.recipients.iter().filter(|r| members.contains(&fingerprint(*r))).cloned().collect())
+            }
+        }
+    }
+
+    fn group_of_hash(&self, h: &[u8; 32]) -> Option<String>
+    {
+        match self.metadata.get(h)
+        {
+            Some(m) => m.group.clone(),
+            None => None
+        }
+    }
+
+    /*
+        Encrypt to the locker's recipients (in group) if it has any, 
+            otherwise to recipient alone
+    */
+    fn encrypt_for(&self, data: &[u8], recipient: &PKey<Public>, group: &Option<String>) -> Result<Vec<u8>, RecipientError>
+    {
+        match self.recipients.is_empty()
+        {
+            true => Ok(encrypt_to(recipient, data)),
+            false => Ok(seal(&self.audience(group)?, data))
+        }
+    }
+
+    /*
+        Fingerprint of the key a single key locker is encrypted to,
+            None for lockers written before it was recorded
+    */
+    pub fn owner(&self) -> Option<String>
+    {
+        self.owner.map(|o| dump_bytes(&o))
+    }
+
+    pub fn owner_key(&self) -> Option<KeyInfo>
+    {
+        self.owner_key.clone()
+    }
+
+    /*
+        Record identity as the owner of a locker written before owners 
+            were, if it decrypts an existing key. Returns whether 
+            identity is (now) the owner
+    */
+    pub fn claim_owner<I: Identity>(&mut self, identity: I) -> bool
+    {
+        let identity = identity.private();
+        let fp = fingerprint(&identity);
+
+        if self.owner.is_none() && self.recipients.is_empty() && self.rotating_to.is_none() && self.keys.first().is_some_and(|k| try_decrypt_string(k, &identity).is_some())
+        {
+            self.owner = Some(fp);
+        }
+
+        if self.owner == Some(fp) && self.owner_key.is_none()
+        {
+            self.owner_key = Some(KeyInfo::of(&identity));
+        }
+
+        self.owner == Some(fp)
+    }
+
+    /*
+        An empty locker owned by the key derived from pass with kdf, 
+            and that key
+    */
+    pub fn with_passphrase(pass: &str, kdf: PassphraseKdf) -> Result<(Locker, PKey<Private>), KdfError>
+    {
+        let identity = kdf.derive(pass)?;
+        let mut lkr = Locker::new();
+        lkr.owner = Some(fingerprint(&identity));
+        lkr.owner_key = Some(KeyInfo::of(&identity));
+        lkr.kdf = Some(kdf);
+        Ok((lkr, identity))
+    }
+
+    /*
+        The kdf of a passphrase locker, None if its key is a PEM
+    */
+    pub fn kdf(&self) -> Option<PassphraseKdf>
+    {
+        self.kdf.clone()
+    }
+
+    /*
+        Derive a passphrase locker's key, a wrong passphrase derives
+            a key which does not own the locker
+    */
+    pub fn unlock(&self, pass: &str) -> Result<PKey<Private>, KdfError>
+    {
+        let identity = match &self.kdf
+        {
+            Some(kdf) => kdf.derive(pass)?,
+            None => { return Err(KdfError { why: "locker is not passphrase protected".to_string() }); }
+        };
+
+        match self.verify_key(&identity)
+        {
+            Ok(_) => Ok(identity),
+            Err(_) => Err(KdfError { why: "incorrect passphrase".to_string() })
+        }
+    }
+
+    /*
+        Fingerprints and sizes of the owner and recipient RSA keys 
+            smaller than min_bits
+    */
+    pub fn weak_keys(&self, min_bits: u32) -> Vec<(String, u32)>
+    {
+        let weak_rsa = |key: &KeyInfo| key.algorithm == KeyAlgorithm::Rsa.name() && key.bits < min_bits;
+
+        let mut weak: Vec<(String, u32)> = self.recipients.iter()
+            .map(|r| (dump_bytes(&fingerprint(r)), KeyInfo::of(r)))
+            .filter(|(_, key)| weak_rsa(key))
+            .map(|(fp, key)| (fp, key.bits))
+            .collect();
+
+        if let (Some(owner), Some(key)) = (self.owner, &self.owner_key)
+        {
+            if weak_rsa(key) && !weak.iter().any(|(fp, _)| *fp == dump_bytes(&owner))
+            {
+                weak.push((dump_bytes(&owner), key.bits));
+            }
+        }
+
+        weak
+    }
+
+    /*
+        Check key is the locker's owner (or the key it is being 
+            rotated to), or one of its recipients, before attempting
+            any decryption
+    */
+    pub fn verify_key<K: Recipient>(&self, key: &K) -> Result<(), KeyMismatchError>
+    {
+        let fp = fingerprint(key);
+
+        if !self.recipients.is_empty()
+        {
+            return match self.recipients.iter().any(|r| fingerprint(r) == fp)
+            {
+                true => Ok(()),
+                false => Err(KeyMismatchError { expected: self.recipients().join(" or "), supplied: dump_bytes(&fp) })
+            }
+        }
+
+        if self.rotating_to == Some(fp)
+        {
+            return Ok(())
+        }
+
+        match self.owner
+        {
+            Some(owner) if owner != fp => 
+            {
+                Err(KeyMismatchError { expected: dump_bytes(&owner), supplied: dump_bytes(&fp) })
+            },
+            _ => Ok(())
         }
     }
-}
 
-impl Into<Entry0_2_0> for Entry
-{
-    fn into(self) -> Entry0_2_0
+    pub fn has_recovery(&self) -> bool
+    {
+        self.recovery.is_some()
+    }
+
+    /*
+        Escrow a new recovery code, returned to be stored offline. Its 
+            key is added as a recipient, so entries outside of groups
+            may be recovered
+    */
+    pub fn add_recovery<I: Identity>(&mut self, identity: I) -> Result<String, RecipientError>
+    {
+        if self.recovery.is_some()
+        {
+            return Err(RecipientError { why: "lkr file already has a recovery code".to_string() });
+        }
+
+        if self.rotating_to.is_some()
+        {
+            return Err(RecipientError { why: "a rotation is in progress, finish it with --re_key first".to_string() });
+        }
+
+        let code = recovery_code();
+        let kdf = PassphraseKdf::scrypt(SCRYPT_LOG_N);
+
+        let key = match kdf.derive(&normalise_recovery_code(&code))
+        {
+            Ok(k) => k,
+            Err(e) => { return Err(RecipientError { why: format!("{}", e) }); }
+        };
+
+        let identity = identity.private();
+
+        // the key recover replaces, for lockers written before owners were recorded
+        if self.owner.is_none()
+        {
+            self.owner = Some(fingerprint(&identity));
+            self.owner_key = Some(KeyInfo::of(&identity));
+        }
+
+        self.add_recipient(&key, identity)?;
+        self.recovery = Some(kdf);
+        Ok(code)
+    }
+
+    /*
+        The key escrowed with code
+    */
+    pub fn recovery_identity(&self, code: &str) -> Result<PKey<Private>, KdfError>
+    {
+        let identity = match &self.recovery
+        {
+            Some(kdf) => kdf.derive(&normalise_recovery_code(code))?,
+            None => { return Err(KdfError { why: "lkr file has no recovery code".to_string() }); }
+        };
+
+        let fp = fingerprint(&identity);
+        match self.recipients.iter().any(|r| fingerprint(r) == fp)
+        {
+            true => Ok(identity),
+            false => Err(KdfError { why: "incorrect recovery code".to_string() })
+        }
+    }
+
+    /*
+        Replace the lost owning key with new, using the recovery key.
+            The code is spent, both it and the old key are removed
+            as recipients, other recipients are kept
+    */
+    pub fn recover<I: Identity, K: Recipient>(&mut self, recovery: I, new: K) -> Result<(), RecipientError>
+    {
+        let (recovery, new) = (recovery.private(), new.public());
+        let recovery_fp = fingerprint(&recovery);
+        let new_fp = fingerprint(&new);
+
+        if self.recovery.is_none() || !self.recipients.iter().any(|r| fingerprint(r) == recovery_fp)
+        {
+            return Err(RecipientError { why: "recovery key is not a recipient in lkr file".to_string() });
+        }
+
+        if !self.recipients.iter().any(|r| fingerprint(r) == new_fp)
+        {
+            self.add_recipient(&new, &recovery)?;
+        }
+
+        if let Some(owner) = self.owner
+        {
+            if owner != new_fp && self.recipients.iter().any(|r| fingerprint(r) == owner)
+            {
+                self.remove_recipient(&dump_bytes(&owner), &recovery, false)?;
+            }
+        }
+
+        self.remove_recipient(&dump_bytes(&recovery_fp), &recovery, false)?;
+
+        self.owner = Some(new_fp);
+        self.owner_key = Some(KeyInfo::of(&new));
+        self.kdf = None;
+        self.recovery = None;
+        Ok(())
+    }
+
+    pub fn split_key(&self) -> Option<SplitKey>
+    {
+        self.split.clone()
+    }
+
+    /*
+        Replace identity with a random key, returning its private key 
+            split into shares, any threshold of which unlock the 
+            locker (see unlock_shares)
+
+        So that no single key opens the locker, identity is removed
+            and the split key becomes the only recipient (and owner).
+            A locker with other recipients or groups is refused
+    */
+    pub fn split<I: Identity>(&mut self, identity: I, shares: u8, threshold: u8) -> Result<Vec<String>, RecipientError>
+    {
+        if self.split.is_some()
+        {
+            return Err(RecipientError { why: "lkr file already has a split key".to_string() });
+        }
+
+        if self.rotating_to.is_some()
+        {
+            return Err(RecipientError { why: "a rotation is in progress, finish it with --re_key first".to_string() });
+        }
+
+        let identity = identity.private();
+        let identity_fp = fingerprint(&identity);
+
+        let others: Vec<String> = self.recipients().into_iter().filter(|r| *r != dump_bytes(&identity_fp)).collect();
+        if !others.is_empty()
+        {
+            return Err(RecipientError { why: format!("recipients {} could open the locker without the shares, remove them first", others.join(", ")) });
+        }
+
+        if !self.groups.is_empty()
+        {
+            return Err(RecipientError { why: "lkr file has groups, which are not readable with a split key".to_string() });
+        }
+
+        let mut secret = [0; 32];
+        rand_bytes(&mut secret).unwrap();
+
+        let parts = match shamir::split(&secret, shares, threshold)
+        {
+            Ok(p) => p,
+            Err(e) => { return Err(RecipientError { why: format!("{}", e) }); }
+        };
+
+        let key = PKey::private_key_from_raw_bytes(&secret, Id::X25519).unwrap();
+        wipe(&mut secret);
+
+        self.add_recipient(&key, &identity)?;
+        self.remove_recipient(&dump_bytes(&identity_fp), &key, false)?;
+        self.owner = Some(fingerprint(&key));
+        self.owner_key = Some(KeyInfo::of(&key));
+        self.split = Some(SplitKey { fingerprint: fingerprint(&key).to_vec(), shares, threshold });
+
+        Ok(parts.iter().map(|(x, bytes)| share_string(*x, threshold, bytes)).collect())
+    }
+
+    /*
+        The split key, reconstructed from at least threshold shares
+    */
+    pub fn unlock_shares(&self, shares: &[String]) -> Result<PKey<Private>, ShamirError>
+    {
+        let split = match &self.split
+        {
+            Some(s) => s,
+            None => { return Err(ShamirError { why: "lkr file has no split key".to_string() }); }
+        };
+
+        let mut parts: Vec<(u8, Vec<u8>)> = Vec::new();
+
+        for share in shares
+        {
+            let (x, threshold, bytes) = parse_share(share)?;
+            if threshold != split.threshold
+            {
+                return Err(ShamirError { why: format!("share {} is from a split with threshold {}, not {}", x, threshold, split.threshold) });
+            }
+            parts.push((x, bytes));
+        }
+
+        if parts.len() < split.threshold as usize
+        {
+            return Err(ShamirError { why: format!("{} of {} shares are needed, {} given", split.threshold, split.shares, parts.len()) });
+        }
+
+        let secret = shamir::combine(&parts)?;
+
+        match PKey::private_key_from_raw_bytes(&secret, Id::X25519)
+        {
+            Ok(key) if fingerprint(&key).to_vec() == split.fingerprint => Ok(key),
+            _ => Err(ShamirError { why: "shares do not reconstruct the split key".to_string() })
+        }
+    }
+
+    /*
+        Fingerprints (SHA-256 of the public key) of each recipient
+    */
+    pub fn recipients(&self) -> Vec<String>
+    {
+        self.recipients.iter().map(|r| dump_bytes(&fingerprint(r))).collect()
+    }
+
+    /*
+        Decrypt all keys and values, e.g. before changing recipients,
+            anything identity cannot read is None
+    */
+    fn plain_text(&self, identity: &PKey<Private>) -> (Vec<Option<SecretString>>, PlainData)
+    {
+        let plain_keys = self.keys.iter().map(|k| try_decrypt_string(k, identity)).collect();
+        let mut plain_data: PlainData = Vec::new();
+        for (h, value) in &self.data
+        {
+            plain_data.push((*h, try_decrypt_string(value, identity)));
+        }
+        (plain_keys, plain_data)
+    }
+
+    /*
+        Seal all readable keys and values to the current recipients 
+            (of their group), with fresh data keys
+
+        Unreadable envelopes are kept, but lose any removed recipient
+    */
+    fn reseal(&mut self, plain_keys: Vec<Option<SecretString>>, plain_data: PlainData, removed: Option<[u8; 32]>) -> Result<(), RecipientError>
+    {
+        for (i, key) in plain_keys.iter().enumerate()
+        {
+            self.keys[i] = match key
+            {
+                Some(k) => seal(&self.audience(&self.group_of_hash(&hash(k)))?, k.as_bytes()),
+                None => match removed 
+                {
+                    Some(fp) => strip_recipient(&self.keys[i], &fp),
+                    None => self.keys[i].clone()
+                }
+            };
+        }
+
+        for (h, value) in plain_data
+        {
+            let sealed = match value
+            {
+                Some(v) => seal(&self.audience(&self.group_of_hash(&h))?, v.as_bytes()),
+                None => match removed 
+                {
+                    Some(fp) => strip_recipient(self.data.get(&h).unwrap(), &fp),
+                    None => continue
+                }
+            };
+            self.data.insert(h, sealed);
+            // envelopes carry the fingerprints of their recipients
+            self.set_key_id(&h, None);
+        }
+
+        Ok(())
+    }
+
+    fn set_key_id(&mut self, h: &[u8; 32], key_id: Option<String>)
+    {
+        let mut metadata = match self.metadata.get(h)
+        {
+            Some(m) => m.clone(),
+            None => Metadata::default()
+        };
+
+        metadata.key_id = key_id;
+
+        match metadata.is_empty()
+        {
+            true => { self.metadata.remove(h); },
+            false => { self.metadata.insert(*h, metadata); }
+        }
+    }
+
+    /*
+        Fingerprint of the key an entry's value is encrypted to, for
+            single key lockers
+    */
+    pub fn key_id(&self, key: &str) -> Option<String>
+    {
+        match self.metadata.get(&hash(key))
+        {
+            Some(m) => m.key_id.clone(),
+            None => None
+        }
+    }
+
+    /*
+        Fingerprint of the key a rotation (see rekey_next) is in 
+            progress to
+    */
+    pub fn rotation(&self) -> Option<String>
+    {
+        self.rotating_to.map(|r| dump_bytes(&r))
+    }
+
+    /*
+        Begin rotating a single key locker to new, resuming if a
+            rotation to new is already in progress
+    */
+    pub fn start_rotation<K: Recipient>(&mut self, new: &K) -> Result<(), RotationError>
+    {
+        let fp = fingerprint(new);
+
+        if !self.recipients.is_empty()
+        {
+            return Err(RotationError { why: "locker has recipients, use add_recipient and remove_recipient".to_string() });
+        }
+
+        match self.rotating_to
+        {
+            Some(r) if r != fp => 
+            {
+                Err(RotationError { why: format!("rotation to {} is in progress, not {}", dump_bytes(&r), dump_bytes(&fp)) })
+            },
+            _ => 
+            {
+                self.rotating_to = Some(fp);
+                Ok(())
+            }
+        }
+    }
+
+    /*
+        Re-encrypt the next entry (key and value) not already 
+            encrypted to new, returning the number of entries 
+            encrypted to new and the total, or None once every
+            entry has been
+
+        Entries are only re-encrypted one at a time, so the locker
+            may be written between calls and the rotation resumed.
+            Each call continues from the entry after the last, so a 
+            rotation decrypts each entry once
+    */
+    pub fn rekey_next<I: Identity, K: Recipient>(&mut self, old: &I, new: &K) -> Result<Option<(usize, usize)>, RotationError>
+    {
+        let (old, new) = (old.private(), new.public());
+        let fp = fingerprint(&new);
+        let new_id = Some(dump_bytes(&fp));
+
+        if self.rotating_to != Some(fp)
+        {
+            return Err(RotationError { why: format!("no rotation to {} in progress", dump_bytes(&fp)) });
+        }
+
+        let total = self.data.len();
+        let done = |lkr: &Locker| lkr.data.keys().filter(|h| lkr.metadata.get(*h).is_some_and(|m| m.key_id == new_id)).count();
+
+        let n = self.keys.len();
+
+        // wrapping around once, to pick up entries skipped by a delete
+        for i in (0..n).map(|j| (self.rekey_cursor+j) % n)
+        {
+            let key = match try_decrypt_string(&self.keys[i], &old)
+            {
+                Some(k) => k,
+                None => continue
+            };
+
+            let h = hash(&key);
+
+            if !self.data.contains_key(&h) || self.key_id(&key) == new_id
+            {
+                continue
+            }
+
+            let value = match try_decrypt_string(self.data.get(&h).unwrap(), &old)
+            {
+                Some(v) => v,
+                None => 
+                {
+                    return Err(RotationError { why: format!("could not decrypt the value of {}", key.as_str()) });
+                }
+            };
+
+            self.keys[i] = encrypt_to(&new, key.as_bytes());
+            self.data.insert(h, encrypt_to(&new, value.as_bytes()));
+            self.set_key_id(&h, new_id.clone());
+            self.rekey_cursor = i+1;
+
+            return Ok(Some((done(self), total)))
+        }
+
+        if done(self) != total
+        {
+            return Err(RotationError { why: format!("{} entries could not be decrypted with the old key", total-done(self)) });
+        }
+
+        self.owner = Some(fp);
+        self.owner_key = Some(KeyInfo::of(&new));
+        // new is not derived from the passphrase
+        self.kdf = None;
+        self.rotating_to = None;
+        Ok(None)
+    }
+
+    /*
+        Add a recipient, all keys and values (outside of groups) are 
+            re-sealed so that the new recipient's private key may 
+            read them
+
+        The first recipient added to a single key locker is 
+            always preceded by the owning key, identity
+    */
+    pub fn add_recipient<K: Recipient, I: Identity>(&mut self, recipient: K, identity: I) -> Result<(), RecipientError>
+    {
+        let (recipient, identity) = (recipient.public(), identity.private());
+        let fp = fingerprint(&recipient);
+        if self.recipients.iter().any(|r| fingerprint(r) == fp)
+        {
+            return Err(RecipientError { why: format!("recipient {} is already in lkr file", dump_bytes(&fp)) });
+        }
+
+        if self.recipients.len().max(1) >= MAX_RECIPIENTS
+        {
+            return Err(RecipientError { why: format!("lkr file already has the maximum of {} recipients", MAX_RECIPIENTS) });
+        }
+
+        if self.recipients.is_empty()
+        {
+            self.recipients.push(identity.public());
+        }
+
+        let (plain_keys, plain_data) = self.plain_text(&identity);
+
+        if !self.recipients.iter().any(|r| fingerprint(r) == fp)
+        {
+            self.recipients.push(recipient);
+        }
+
+        self.reseal(plain_keys, plain_data, None)
+    }
+
+    /*
+        Remove the recipient with fingerprint, every key and value
+            is re-sealed under a fresh data key for the remaining
+            recipients
+
+        Entries identity cannot read (in another group) can only have 
+            the removed recipient's wrapped key dropped
+
+        The removed recipient may have seen any value, so each
+            can be marked as needing rotation
+    */
+    pub fn remove_recipient<I: Identity>(&mut self, fingerprint_hex: &str, identity: I, mark_rotation: bool) -> Result<(), RecipientError>
+    {
+        let index = match self.recipients.iter().position(|r| dump_bytes(&fingerprint(r)).eq_ignore_ascii_case(fingerprint_hex))
+        {
+            Some(i) => i,
+            None => 
+            {
+                return Err(RecipientError { why: format!("recipient {} is not in lkr file", fingerprint_hex) });
+            }
+        };
+
+        if self.recipients.len() == 1
+        {
+            return Err(RecipientError { why: format!("cannot remove {}, the last recipient", fingerprint_hex) });
+        }
+
+        let fp = fingerprint(&self.recipients[index]);
+
+        for (name, members) in &self.groups
+        {
+            if members.len() == 1 && members[0] == fp
+            {
+                return Err(RecipientError { why: format!("cannot remove {}, the last member of group {}", fingerprint_hex, name) });
+            }
+        }
+
+        let (plain_keys, plain_data) = self.plain_text(&identity.private());
+
+        self.recipients.remove(index);
+        if self.split.as_ref().is_some_and(|s| s.fingerprint == fp)
+        {
+            self.split = None;
+        }
+        for members in self.groups.values_mut()
+        {
+            members.retain(|m| *m != fp);
+        }
+
+        if mark_rotation
+        {
+            for (h, _) in &plain_data
+            {
+                self.metadata.entry(*h).or_default().needs_rotation = true;
+            }
+        }
+
+        self.reseal(plain_keys, plain_data, Some(fp))
+    }
+
+    /*
+        Add a named group of recipients (by fingerprint), entries 
+            inserted to the group may only be read by its members
+    */
+    pub fn add_group(&mut self, name: &str, members: Vec<String>) -> Result<(), RecipientError>
+    {
+        if self.groups.contains_key(name)
+        {
+            return Err(RecipientError { why: format!("group {} is already in lkr file", name) });
+        }
+
+        if members.is_empty()
+        {
+            return Err(RecipientError { why: format!("group {} has no members", name) });
+        }
+
+        let mut fingerprints: Vec<[u8; 32]> = Vec::new();
+
+        for member in members
+        {
+            match self.recipients.iter().find(|r| dump_bytes(&fingerprint(*r)).eq_ignore_ascii_case(&member))
+            {
+                Some(r) => fingerprints.push(fingerprint(r)),
+                None => 
+                {
+                    return Err(RecipientError { why: format!("group member {} is not a recipient in lkr file", member) });
+                }
+            }
+        }
+
+        self.groups.insert(name.to_string(), fingerprints);
+        Ok(())
+    }
+
+    /*
+        Each group's name and member fingerprints
+    */
+    pub fn groups(&self) -> Vec<(String, Vec<String>)>
+    {
+        self.groups.iter().map(|(name, members)| (name.clone(), members.iter().map(|m| dump_bytes(m)).collect())).collect()
+    }
+
+    pub fn group_of(&self, key: &str) -> Option<String>
+    {
+        self.group_of_hash(&hash(key))
+    }
+
+    /*
+        When the value of key was last inserted (unix seconds), if 
+            recorded
+    */
+    pub fn modified(&self, key: &str) -> Option<u64>
+    {
+        match self.metadata.get(&hash(key))
+        {
+            Some(m) => m.modified,
+            None => None
+        }
+    }
+
+    /*
+        Whether the value of key has been marked as needing rotation,
+            e.g. after a recipient was removed
+    */
+    pub fn needs_rotation(&self, key: &str) -> bool
+    {
+        match self.metadata.get(&hash(key))
+        {
+            Some(m) => m.needs_rotation,
+            None => false
+        }
+    }
+
+    /*
+        Whether the value of key is a json object of fields
+    */
+    pub fn is_structured(&self, key: &str) -> bool
+    {
+        match self.metadata.get(&hash(key))
+        {
+            Some(m) => m.structured,
+            None => false
+        }
+    }
+
+    fn set_structured(&mut self, key: &str, structured: bool)
+    {
+        if let Some(m) = self.metadata.get_mut(&hash(key))
+        {
+            m.structured = structured;
+        }
+    }
+
+    pub fn contains(&self, key: &str) -> bool
+    {
+        let h = hash(key);
+        self.data.contains_key(&h)
+    }
+
+    pub fn index_of<I: Identity>(&self, key: &str, identity: I) -> Option<usize>
+    {
+        let identity = identity.private();
+        self.keys.iter().position(|x| try_decrypt_string(x, &identity).is_some_and(|k| k == key))
+    }
+
+    /*
+        Inserting only requires the public key, so recipient may be
+            a public key loaded from a .pub file
+    */
+    pub fn insert<K: Recipient>(&mut self, key: &str, value: &str, recipient: K, overwrite: bool) -> Result<(), KeyCollisionError>
+    {
+        self.insert_in_group(key, value, recipient, overwrite, None)
+    }
+
+    /*
+        Insert an entry readable only by the members of group
+
+        Overwriting keeps an entry's group if group is None, moving
+            an entry to another group is a collision
+    */
+    pub fn insert_in_group<K: Recipient>(&mut self, key: &str, value: &str, recipient: K, overwrite: bool, group: Option<&str>) -> Result<(), KeyCollisionError>
+    {
+        let recipient = recipient.public();
+        let contains_key = self.contains(key);
+        let h = hash(key);
+        let current_group = self.group_of_hash(&h);
+
+        if contains_key && !overwrite
+        {
+            return Err(KeyCollisionError {key: key.to_string()})
+        }
+
+        let group = match (group, contains_key)
+        {
+            (None, true) => current_group,
+            (Some(g), true) if current_group.as_deref() != Some(g) =>
+            {
+                return Err(KeyCollisionError {key: format!("{} (outside of group {})", key, g)})
+            },
+            (g, _) => g.map(|g| g.to_string())
+        };
+
+        if let Some(g) = &group
+        {
+            if !self.groups.contains_key(g)
+            {
+                return Err(KeyCollisionError {key: format!("{} (no group {} in lkr file)", key, g)})
+            }
+        }
+
+        // an older locker's owner is only recorded by claim_owner, as
+        //  recipient may be any public key
+        if self.recipients.is_empty() && self.owner.is_none() && self.keys.is_empty()
+        {
+            self.owner = Some(fingerprint(&recipient));
+        }
+
+        if self.owner == Some(fingerprint(&recipient)) && self.owner_key.is_none()
+        {
+            self.owner_key = Some(KeyInfo::of(&recipient));
+        }
+
+        let no_group = |e: RecipientError| KeyCollisionError {key: format!("{} ({})", key, e.why)};
+        if !contains_key
+        {
+            let sealed = self.encrypt_for(key.as_bytes(), &recipient, &group).map_err(no_group)?;
+            self.keys.push(sealed);
+        }
+        let sealed = self.encrypt_for(value.as_bytes(), &recipient, &group).map_err(no_group)?;
+        self.data.insert(h, sealed);
+
+        let key_id = match self.recipients.is_empty()
+        {
+            true => Some(dump_bytes(&fingerprint(&recipient))),
+            false => None
+        };
+
+        let metadata = Metadata { group, key_id, modified: Some(now()), ..Metadata::default() };
+        match metadata.is_empty()
+        {
+            true => { self.metadata.remove(&h); },
+            false => { self.metadata.insert(h, metadata); }
+        }
+
+        Ok(())
+    }
+
+    /*
+        Insert a json object of fields (which may themselves be
+            objects) as key
+    */
+    pub fn insert_structured<K: Recipient>(&mut self, key: &str, fields: &serde_json::Value, recipient: K, overwrite: bool) -> Result<(), FieldError>
+    {
+        if !fields.is_object()
+        {
+            return Err(FieldError { why: format!("fields of {} are not a json object", key) });
+        }
+
+        let value = secret_json(fields);
+        match self.insert_in_group(key, &value, recipient, overwrite, None)
+        {
+            Ok(_) => { self.set_structured(key, true); Ok(()) },
+            Err(e) => Err(FieldError { why: format!("{}", e) })
+        }
+    }
+
+    /*
+        Set fields (path, value) of key, creating the entry (or nested 
+            objects, for a path like conn.host) if needed. Other 
+            fields are kept, a key holding a plain value is an error
+    */
+    pub fn set_fields<I: Identity>(&mut self, key: &str, fields: &[(String, SecretString)], identity: I) -> Result<(), FieldError>
+    {
+        let identity = identity.private();
+
+        let mut object = SecretJson::new(match (self.contains(key), self.is_structured(key))
+        {
+            (false, _) => serde_json::Value::Object(serde_json::Map::new()),
+            (true, false) => { return Err(FieldError { why: format!("key {} holds a value, not fields", key) }); },
+            (true, true) => match self.get_fields(key, &identity)
+            {
+                Ok(o) => o,
+                Err(e) => { return Err(FieldError { why: format!("{}", e) }); }
+            }
+        });
+
+        for (path, value) in fields
+        {
+            let mut current: &mut serde_json::Value = &mut object;
+            let names: Vec<&str> = path.split(FIELD_SEPARATOR).collect();
+
+            for (i, name) in names.iter().enumerate()
+            {
+                let map = match current.as_object_mut()
+                {
+                    Some(m) => m,
+                    None => { return Err(FieldError { why: format!("field {} of {} is not an object", names[0..i].join("."), key) }); }
+                };
+
+                current = match i+1 == names.len()
+                {
+                    true => 
+                    {
+                        // a replaced value is wiped as it is dropped
+                        let _ = map.insert(name.to_string(), serde_json::Value::String(value.to_string())).map(SecretJson::new);
+                        break;
+                    },
+                    false => map.entry(name.to_string()).or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+                };
+            }
+        }
+
+        self.insert_structured(key, &object, &identity, true)
+    }
+
+    /*
+        The fields of a structured key, as a json object
+    */
+    pub fn get_fields<I: Identity>(&self, key: &str, identity: I) -> Result<serde_json::Value, KeyNonExistantError>
+    {
+        if !self.is_structured(key)
+        {
+            return Err(KeyNonExistantError { key: format!("{} (holds a value, not fields)", key) });
+        }
+
+        let value = self.get(key, identity)?;
+        match serde_json::from_str(&value)
+        {
+            Ok(v) => Ok(v),
+            Err(_) => Err(KeyNonExistantError { key: format!("{} (fields are not valid json)", key) })
+        }
+    }
+
+    /*
+        The value of key, or of a field within a structured key, e.g.
+            db.password is field password of db (unless db.password 
+            is itself a key). Fields that are not strings are given
+            as json
+    */
+    pub fn get_field<I: Identity>(&self, path: &str, identity: I) -> Result<SecretString, KeyNonExistantError>
+    {
+        if self.contains(path)
+        {
+            return self.get(path, identity);
+        }
+
+        // the longest key which path is a field of
+        let splits = path.match_indices(FIELD_SEPARATOR).map(|(i, _)| i).rev();
+
+        for i in splits
+        {
+            let (key, field) = (&path[0..i], &path[i+1..]);
+
+            if !self.is_structured(key)
+            {
+                continue;
+            }
+
+            let fields = self.get_fields(key, identity)?;
+
+            return match field_at(&fields, field)
+            {
+                Some(serde_json::Value::String(s)) => Ok(SecretString::new(s.clone())),
+                Some(v) => Ok(SecretString::new(v.to_string())),
+                None => Err(KeyNonExistantError { key: format!("{} (no field {} in {})", path, field, key) })
+            };
+        }
+
+        Err(KeyNonExistantError { key: path.to_string() })
+    }
+
+    pub fn delete<I: Identity>(&mut self, key: &str, identity: I) -> Result<(), KeyNonExistantError>
+    {
+        match self.index_of(key, identity)
+        {
+            Some(index) if self.contains(key) => 
+            {
+                self.data.remove(&hash(key));
+                self.metadata.remove(&hash(key));
+                self.keys.remove(index);
+                Ok(())
+            },
+            _ => 
+            {
+                Err(KeyNonExistantError { key: format!("no key to delete: {}", key) })
+            }
+        }
+    }
+
+    pub fn get<I: Identity>(&self, key: &str, identity: I) -> Result<SecretString, KeyNonExistantError>
+    {
+        let identity = identity.private();
+        match self.contains(key) 
+        {
+            false => Err(KeyNonExistantError {key: key.to_string()}),
+            true =>
+            {
+                let h = hash(key);
+
+                if let Some(id) = self.key_id(key)
+                {
+                    if id != dump_bytes(&fingerprint(&identity))
+                    {
+                        return Err(KeyNonExistantError {key: format!("{} (encrypted to key {})", key, id)})
+                    }
+                }
+
+                let data = self.data.get(&h).unwrap();
+                match try_decrypt_string(data, &identity)
+                {
+                    Some(value) => Ok(value),
+                    None => Err(KeyNonExistantError {key: format!("{} (not readable with this key)", key)})
+                }
+            }
+        }
+    }
+
+    /*
+        All keys identity can read, those in other groups are hidden
+    */
+    pub fn get_keys<I: Identity>(&self, identity: I) -> Vec<String>
+    {
+        let identity = identity.private();
+        let mut keys: Vec<String> = Vec::new();
+        for key in &self.keys
+        {   
+            if let Some(k) = try_decrypt_string(key, &identity)
+            {
+                keys.push(k.to_string());
+            }
+        }
+        keys
+    }
+
+    /*
+        The number of keys identity cannot read
+    */
+    pub fn hidden_keys<I: Identity>(&self, identity: I) -> usize
+    {
+        self.keys.len()-self.get_keys(identity).len()
+    }
+
+    /*
+        The keys identity can read which match pattern (all, if 
+            none), in order. Only the keys are decrypted, not their
+            values
+    */
+    pub fn list_keys<I: Identity>(&self, identity: I, pattern: Option<&Regex>, order: KeyOrder) -> Vec<String>
+    {
+        let mut keys: Vec<String> = self.get_keys(identity).into_iter().filter(|k| pattern.is_none_or(|p| p.is_match(k))).collect();
+
+        match order
+        {
+            KeyOrder::Name => keys.sort(),
+            KeyOrder::Modified => keys.sort_by(|a, b| self.modified(b).cmp(&self.modified(a)).then(a.cmp(b)))
+        }
+        keys
+    }
+
+    /*
+        The keys identity can read in namespace (and namespaces
+            within it), by name
+    */
+    pub fn namespace_keys<I: Identity>(&self, identity: I, namespace: &str) -> Vec<String>
+    {
+        let mut keys: Vec<String> = self.get_keys(identity).into_iter().filter(|k| strip_namespace(k, namespace).is_some()).collect();
+        keys.sort();
+        keys
+    }
+
+    /*
+        Delete every key identity can read in namespace, returning
+            the keys deleted
+    */
+    pub fn delete_namespace<I: Identity>(&mut self, namespace: &str, identity: I) -> Result<Vec<String>, KeyNonExistantError>
+    {
+        let identity = identity.private();
+        let keys = self.namespace_keys(&identity, namespace);
+
+        if keys.is_empty()
+        {
+            return Err(KeyNonExistantError { key: format!("no keys in namespace {}", namespace) });
+        }
+
+        for key in &keys
+        {
+            self.delete(key, &identity)?;
+        }
+
+        Ok(keys)
+    }
+
+    /*
+        Compare with another locker (e.g. a colleague's copy) by 
+            decrypting both with identity
+    */
+    pub fn diff<I: Identity>(&self, other: &Locker, identity: I) -> LockerDiff
+    {
+        let identity = identity.private();
+        let (keys, other_keys) = (self.get_keys(&identity), other.get_keys(&identity));
+        let mut diff = LockerDiff::default();
+
+        for key in &keys
+        {
+            match other_keys.contains(key)
+            {
+                false => diff.only_a.push(key.clone()),
+                true => 
+                {
+                    let (a, b) = (self.get(key, &identity), other.get(key, &identity));
+                    if a.is_ok() != b.is_ok() || a.is_ok_and(|a| a.as_str() != b.unwrap().as_str())
+                    {
+                        diff.different.push(key.clone());
+                    }
+                }
+            }
+        }
+
+        diff.only_b = other_keys.into_iter().filter(|k| !keys.contains(k)).collect();
+        diff
+    }
+
+    /*
+        Insert key from another locker as name, keeping its group (if
+            this locker has it) and modified time. It is read with 
+            identity and encrypted to recipient (or this locker's 
+            recipients), the value is only decrypted in memory
+    */
+    pub fn copy_entry<I: Identity, K: Recipient>(&mut self, from: &Locker, key: &str, name: &str, identity: I, recipient: K, overwrite: bool) -> Result<(), MoveError>
     {
-        Entry0_2_0
-        { 
-            hash: dump_bytes(&self.hash), 
-            value: dump_bytes(&self.value)
+        let value = match from.get(key, identity)
+        {
+            Ok(v) => v,
+            Err(e) => { return Err(MoveError { why: format!("{}", e) }); }
+        };
+
+        let group = from.group_of(key).filter(|g| self.groups.contains_key(g));
+
+        match self.insert_in_group(name, &value, recipient, overwrite, group.as_deref())
+        {
+            Ok(_) => {},
+            Err(e) => { return Err(MoveError { why: format!("{}", e) }); }
+        }
+
+        if let Some(m) = self.metadata.get_mut(&hash(name))
+        {
+            m.modified = from.modified(key);
+            m.structured = from.is_structured(key);
         }
+
+        Ok(())
     }
-}
 
-#[derive(Serialize, Deserialize)]
-pub struct Key 
-{
-    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
-    bytes: Vec<u8>
-}
+    /*
+        Rename key old to new, keeping its value, group and modified 
+            time
+    */
+    pub fn rename<I: Identity>(&mut self, old: &str, new: &str, identity: I) -> Result<(), MoveError>
+    {
+        let identity = identity.private();
 
-#[derive(Serialize, Deserialize)]
-pub struct Lkr
-{
-    version: String,
-    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
-    check_hash: Vec<u8>,
-    entries: Vec<Entry>,
-    keys: Vec<Key>
-}
+        if self.contains(new)
+        {
+            return Err(MoveError { why: format!("key {}, is already in lkr file", new) });
+        }
 
-pub struct Locker {
-    data: HashMap<[u8; 32], Vec<u8>>,
-    keys: Vec<Vec<u8>>
-}
+        let value = match self.get(old, &identity)
+        {
+            Ok(v) => v,
+            Err(e) => { return Err(MoveError { why: format!("{}", e) }); }
+        };
 
-#[derive(Serialize, Deserialize)]
-pub struct EntryPlainText
-{
-    pub key: String,
-    pub value: String
-} 
+        let (group, modified, structured) = (self.group_of(old), self.modified(old), self.is_structured(old));
 
-impl Locker 
-{
+        match self.insert_in_group(new, &value, &identity, false, group.as_deref())
+        {
+            Ok(_) => {},
+            Err(e) => { return Err(MoveError { why: format!("{}", e) }); }
+        }
 
-    pub fn new() -> Locker
-    {
-        Locker { data: HashMap::new(), keys: Vec::new() }
+        if let Some(m) = self.metadata.get_mut(&hash(new))
+        {
+            m.modified = modified;
+            m.structured = structured;
+        }
+
+        match self.delete(old, &identity)
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(MoveError { why: format!("{}", e) })
+        }
     }
 
-    pub fn contains(&self, key: &str) -> bool
+    /*
+        Everything but the entries and keys, to compare lockers
+    */
+    fn header(&self) -> serde_json::Value
     {
-        let h = hash(key);
-        self.data.contains_key(&h)
+        serde_json::json!
+        ({
+            "owner": self.owner.map(|o| dump_bytes(&o)),
+            "owner_key": self.owner_key,
+            "kdf": self.kdf,
+            "recovery": self.recovery,
+            "split": self.split,
+            "rotating_to": self.rotating_to.map(|r| dump_bytes(&r)),
+            "recipients": self.recipients.iter().map(|r| dump_bytes(&r.public_key_to_der().unwrap())).collect::<Vec<String>>(),
+            "groups": self.groups.iter().map(|(name, members)| (name.clone(), members.iter().map(|m| dump_bytes(m)).collect::<Vec<String>>())).collect::<BTreeMap<String, Vec<String>>>()
+        })
     }
 
-    pub fn index_of(&self, key: &str, rsa: Rsa<Private>) -> Option<usize>
+    fn entry(&self, h: &[u8; 32]) -> Option<(&Vec<u8>, Metadata)>
     {
-        self.keys.iter().position(|x| decrypt_string(x.to_vec(), rsa.clone()) == key)
+        self.data.get(h).map(|v| (v, self.metadata.get(h).cloned().unwrap_or_default()))
     }
 
-    pub fn insert(&mut self, key: &str, value: &str, rsa: Rsa<Private>, overwrite: bool) -> Result<(), KeyCollisionError>
+    /*
+        A three-way merge of lockers that diverged from base, e.g. for 
+            git (see --git_merge), nothing is decrypted
+
+        Untouched entries keep their cipher text, so an entry (or 
+            encrypted key) that differs from base has changed. One 
+            changed on one side only is taken from that side, one
+            changed differently on both sides is a conflict
+
+        The header (owner, recipients, groups, ...) may only change 
+            on one side, or the same way on both. Recipients and groups
+            may only change if the other side changed no entries, 
+            since those would be sealed to the old recipients
+    */
+    pub fn merge(base: &Locker, ours: &Locker, theirs: &Locker) -> Result<Locker, MergeError>
     {
-        let contains_key = self.contains(key);
-        if contains_key && !overwrite
+        let entries_changed = |lkr: &Locker| lkr.keys != base.keys || lkr.data.keys().chain(base.data.keys()).any(|h| lkr.entry(h) != base.entry(h));
+        let (o, a, b) = (base.header(), ours.header(), theirs.header());
+        let audience = |h: &serde_json::Value| (h["recipients"].clone(), h["groups"].clone());
+
+        let header = match (a == o, b == o)
         {
-            Err(KeyCollisionError {key: key.to_string()})
-        }
-        else
+            (true, _) => theirs,
+            (false, true) => ours,
+            (false, false) if a == b => ours,
+            (false, false) => 
+            {
+                return Err(MergeError { why: "both sides changed the owner, recipients or groups".to_string() });
+            }
+        };
+
+        let (ao, aa, ab) = (audience(&o), audience(&a), audience(&b));
+
+        if aa != ab && ((aa != ao && entries_changed(theirs)) || (ab != ao && entries_changed(ours)))
         {
-            if !contains_key { self.keys.push(encrypt(rsa.clone(), key.as_bytes())); }
-            let h = hash(key);
-            self.data.insert(h, encrypt(rsa, value.as_bytes()));
-            Ok(())
+            return Err(MergeError { why: "one side changed recipients or groups, the other changed entries, re-insert them after merging".to_string() });
         }
-    }
 
-    pub fn delete(&mut self, key: &str, rsa: Rsa<Private>) -> Result<(), KeyNonExistantError>
-    {
-        match self.contains(key)
+        let mut merged = Locker::new();
+        merged.owner = header.owner;
+        merged.owner_key = header.owner_key.clone();
+        merged.kdf = header.kdf.clone();
+        merged.recovery = header.recovery.clone();
+        merged.split = header.split.clone();
+        merged.rotating_to = header.rotating_to;
+        merged.recipients = header.recipients.clone();
+        merged.groups = header.groups.clone();
+
+        let hashes: BTreeSet<&[u8; 32]> = base.data.keys().chain(ours.data.keys()).chain(theirs.data.keys()).collect();
+
+        for h in hashes
         {
-            true => 
+            let (o, a, b) = (base.entry(h), ours.entry(h), theirs.entry(h));
+
+            let entry = match (a == o, b == o || a == b)
             {
-                let index = self.index_of(key, rsa).unwrap();
-                self.data.remove(&hash(&key));
-                self.keys.remove(index);
-                Ok(())
-            },
-            false => 
+                (true, _) => b,
+                (false, true) => a,
+                (false, false) => 
+                {
+                    return Err(MergeError { why: format!("entry {} was changed on both sides", dump_bytes(h)) });
+                }
+            };
+
+            if let Some((value, metadata)) = entry
             {
-                Err(KeyNonExistantError { key: format!("no key to delete: {}", key) })
+                merged.data.insert(*h, value.clone());
+                if !metadata.is_empty() { merged.metadata.insert(*h, metadata); }
             }
         }
-    }
 
-    pub fn get(&self, key: &str, rsa: Rsa<Private>) -> Result<String, KeyNonExistantError>
-    {
-        match self.contains(&key) 
+        // a key is kept unless deleted on either side
+        for key in ours.keys.iter().chain(theirs.keys.iter())
         {
-            false => Err(KeyNonExistantError {key: key.to_string()}),
-            true =>
+            let (o, a, b) = (base.keys.contains(key), ours.keys.contains(key), theirs.keys.contains(key));
+            if ((a && b) || !o) && !merged.keys.contains(key)
             {
-                let h = hash(&key);
-                let data = self.data.get(&h).unwrap();
-                Ok(decrypt_string(data.to_vec(), rsa))
+                merged.keys.push(key.clone());
             }
         }
+
+        if let Some(g) = merged.metadata.values().find_map(|m| m.group.as_ref().filter(|g| !merged.groups.contains_key(*g)))
+        {
+            return Err(MergeError { why: format!("an entry is in group {}, which was removed", g) });
+        }
+
+        if merged.keys.len() != merged.data.len()
+        {
+            return Err(MergeError { why: format!("merged {} keys for {} entries", merged.keys.len(), merged.data.len()) });
+        }
+
+        Ok(merged)
     }
 
-    pub fn get_keys(&self, rsa: Rsa<Private>) -> Vec<String>
+    /*
+        A line per entry identity can read, its key, group and a short
+            digest of its (encrypted) value, or the value itself, so 
+            that a text diff of two lockers shows changed keys (see
+            --git_diff). Unreadable entries are listed by hash
+    */
+    pub fn describe<I: Identity>(&self, identity: I, values: bool) -> Vec<String>
     {
-        let mut keys: Vec<String> = Vec::new();
+        let identity = identity.private();
+        let mut lines: Vec<String> = Vec::new();
+
+        if let Some(owner) = &self.owner
+        {
+            lines.push(format!("owner {}", dump_bytes(owner)));
+        }
+
+        for r in &self.recipients
+        {
+            lines.push(format!("recipient {}", dump_bytes(&fingerprint(r))));
+        }
+
+        for (name, members) in &self.groups
+        {
+            lines.push(format!("group {} {}", name, members.iter().map(|m| dump_bytes(m)).collect::<Vec<String>>().join(",")));
+        }
+
+        let mut readable: Vec<[u8; 32]> = Vec::new();
+        let mut entries: Vec<String> = Vec::new();
+
         for key in &self.keys
-        {   
-            keys.push(decrypt_string(key.to_vec(), rsa.clone()));
+        {
+            let key = match try_decrypt_string(key, &identity)
+            {
+                Some(k) => k,
+                None => continue
+            };
+
+            let h = hash(&key);
+            let value = match (self.data.get(&h), values)
+            {
+                (None, _) => continue,
+                (Some(v), true) => match try_decrypt_string(v, &identity)
+                {
+                    Some(v) => format!("= {}", v.as_str()),
+                    None => "(not readable)".to_string()
+                },
+                (Some(v), false) => dump_bytes(&sha256(v)[0..4])
+            };
+
+            let group = match self.group_of_hash(&h)
+            {
+                Some(g) => format!(" [{}]", g),
+                None => String::new()
+            };
+
+            readable.push(h);
+            entries.push(format!("{}{} {}", key.as_str(), group, value));
         }
-        keys
+
+        entries.sort();
+
+        for (h, v) in &self.data
+        {
+            if !readable.contains(h)
+            {
+                entries.push(format!("hash {} {}", dump_bytes(h), dump_bytes(&sha256(v)[0..4])));
+            }
+        }
+
+        lines.extend(entries);
+        lines
     }
 
     pub fn read(&mut self, path: &str) -> Result<(), ReadError>
@@ -215,14 +1871,10 @@ impl Locker
             }
         };
 
-        match data.find("\"version\": [")
+        if data.contains("\"version\": [")
         {
-            Some(_) => 
-            {
-                let msg = format!("Incompatible lkr file {}, version 0.1.0, loaded in newer release, {}", path, program_version());
-                return Err(ReadError {why: msg, file: path.to_string()})
-            },
-            None => {}
+            let msg = format!("Incompatible lkr file {}, version 0.1.0, loaded in newer release, {}", path, program_version());
+            return Err(ReadError {why: msg, file: path.to_string()})
         }
 
         let re = Regex::new(VERSION_REGEX).unwrap();
@@ -234,7 +1886,7 @@ impl Locker
 
         compatible(file_version.clone());
 
-        let (lkr_entries, lkr_keys, lkr_check_hash) = if file_version >= version_compression_added()
+        let (lkr_entries, lkr_keys, lkr_check_hash, lkr_recipients, lkr_groups, lkr_owner, lkr_owner_key, lkr_kdf, lkr_recovery, lkr_split, lkr_rotating_to) = if file_version >= version_compression_added()
         {
             let lkr: Lkr = match serde_json::from_str(&data)
             {
@@ -245,7 +1897,7 @@ impl Locker
                 }
             };
             
-            (lkr.entries, lkr.keys, lkr.check_hash)
+            (lkr.entries, lkr.keys, lkr.check_hash, lkr.recipients, lkr.groups, lkr.owner, lkr.owner_key, lkr.kdf, lkr.recovery, lkr.split, lkr.rotating_to)
         }
         else 
         {
@@ -272,11 +1924,65 @@ impl Locker
                 keys.push(Key { bytes: read_bytes(k) });
             }
 
-            (entries, keys, read_bytes(lkr.check_hash))
+            (entries, keys, read_bytes(lkr.check_hash), Vec::new(), Vec::new(), None, None, None, None, None, None)
         };
 
         let mut check_hash: Sha256 = Sha256::new();
 
+        if let Some(owner) = lkr_owner
+        {
+            check_hash.update(&owner);
+
+            match owner.try_into()
+            {
+                Ok(fp) => self.owner = Some(fp),
+                Err(_) => 
+                {
+                    let msg = format!("found owner fingerprint of incorrect size in {}", path);
+                    return Err(ReadError { why: msg, file: path.to_string() })
+                }
+            }
+        }
+
+        if let Some(owner_key) = lkr_owner_key
+        {
+            check_hash.update(&serde_json::to_vec(&owner_key).unwrap());
+            self.owner_key = Some(owner_key);
+        }
+
+        if let Some(kdf) = lkr_kdf
+        {
+            check_hash.update(&serde_json::to_vec(&kdf).unwrap());
+            self.kdf = Some(kdf);
+        }
+
+        if let Some(recovery) = lkr_recovery
+        {
+            check_hash.update(&serde_json::to_vec(&recovery).unwrap());
+            self.recovery = Some(recovery);
+        }
+
+        if let Some(split) = lkr_split
+        {
+            check_hash.update(&serde_json::to_vec(&split).unwrap());
+            self.split = Some(split);
+        }
+
+        if let Some(rotating_to) = lkr_rotating_to
+        {
+            check_hash.update(&rotating_to);
+
+            match rotating_to.try_into()
+            {
+                Ok(fp) => self.rotating_to = Some(fp),
+                Err(_) => 
+                {
+                    let msg = format!("found rotation fingerprint of incorrect size in {}", path);
+                    return Err(ReadError { why: msg, file: path.to_string() })
+                }
+            }
+        }
+
         for entry in lkr_entries
         {
 
@@ -293,7 +1999,15 @@ impl Locker
                 }
             };
 
-            self.data.insert(entry.hash.try_into().unwrap(), entry.value);
+            let h: [u8; 32] = entry.hash.try_into().unwrap();
+
+            if !entry.metadata.is_empty()
+            {
+                check_hash.update(&serde_json::to_vec(&entry.metadata).unwrap());
+                self.metadata.insert(h, entry.metadata);
+            }
+
+            self.data.insert(h, entry.value);
         }
 
         for key in lkr_keys
@@ -302,6 +2016,71 @@ impl Locker
             self.keys.push(key.bytes);
         }
 
+        for recipient in lkr_recipients
+        {
+            check_hash.update(&recipient.fingerprint);
+            check_hash.update(&recipient.public_key);
+
+            let public = match PKey::public_key_from_der(&recipient.public_key)
+            {
+                Ok(p) if KeyAlgorithm::of(&p).is_some() => p,
+                Ok(_) => 
+                {
+                    let msg = format!("recipient {} has an unsupported key type in {}", dump_bytes(&recipient.fingerprint), path);
+                    return Err(ReadError { why: msg, file: path.to_string() })
+                },
+                Err(why) => 
+                {
+                    let msg = format!("could not parse recipient {} in {}: {}", dump_bytes(&recipient.fingerprint), path, why);
+                    return Err(ReadError { why: msg, file: path.to_string() })
+                }
+            };
+
+            if fingerprint(&public).to_vec() != recipient.fingerprint
+            {
+                let msg = format!("recipient {} does not match its public key in {}", dump_bytes(&recipient.fingerprint), path);
+                return Err(ReadError { why: msg, file: path.to_string() })
+            }
+
+            self.recipients.push(public);
+        }
+
+        for group in lkr_groups
+        {
+            check_hash.update(group.name.as_bytes());
+
+            let mut members: Vec<[u8; 32]> = Vec::new();
+
+            for member in group.members
+            {
+                check_hash.update(&member.bytes);
+
+                match member.bytes.try_into()
+                {
+                    Ok(fp) => members.push(fp),
+                    Err(_) => 
+                    {
+                        let msg = format!("found member of group {} with fingerprint of incorrect size in {}", group.name, path);
+                        return Err(ReadError { why: msg, file: path.to_string() })
+                    }
+                }
+            }
+
+            if !members.iter().all(|m| self.recipients.iter().any(|r| fingerprint(r) == *m))
+            {
+                let msg = format!("group {} has members which are not recipients in {}", group.name, path);
+                return Err(ReadError { why: msg, file: path.to_string() })
+            }
+
+            self.groups.insert(group.name, members);
+        }
+
+        if let Some(g) = self.metadata.values().find_map(|m| m.group.as_ref().filter(|g| !self.groups.contains_key(*g)))
+        {
+            let msg = format!("found entry in group {}, which is not in {}", g, path);
+            return Err(ReadError { why: msg, file: path.to_string() })
+        }
+
         if lkr_check_hash != check_hash.finish()
         {
             warning(format!("Computed hash from {} does not match check hash in file, possible manipulation",path).as_str());
@@ -312,26 +2091,133 @@ impl Locker
 
     pub fn write(&self, path: &str) -> Result<(), WriteError>
     {
+        Locker::write_all(&[(self, path)])
+    }
 
+    fn backup(path: &str) -> Result<(), WriteError>
+    {
         if Path::new(path).exists()
         {
-            match std::fs::copy(path, format!("{}.bk",path))
+            // copy keeps the mode of the original, which may predate 0600
+            match std::fs::copy(path, format!("{}.bk",path)).and_then(|_| restrict_permissions(&format!("{}.bk",path)))
             {
                 Ok(_) => {},
                 Err(why) => {return Err(WriteError{ why: format!("Error when backing up lkr file: {}", why), file: path.to_string()})}
             }
         }
+        Ok(())
+    }
+
+    /*
+        Replace path with contents, written to a temporary file first
+            so that path is never left half written
+    */
+    fn replace(path: &str, contents: &str) -> Result<(), WriteError>
+    {
+        let tmp = format!("{}.tmp", path);
+        match try_write_file(&tmp, contents.as_bytes())
+        {
+            Ok(_) => {},
+            Err(why) => { return Err(WriteError { why: format!("Error when writing lkr file: {}", why), file: tmp }); }
+        }
+
+        match std::fs::rename(&tmp, path)
+        {
+            Ok(_) => Ok(()),
+            Err(why) => Err(WriteError { why: format!("Error when replacing lkr file: {}", why), file: path.to_string() })
+        }
+    }
+
+    /*
+        Write several lockers (backing each up), all are serialised 
+            before any file changes. Order them so that stopping 
+            between files is harmless, e.g. the destination of a 
+            moved entry before its source, leaving it in both
+    */
+    pub fn write_all(lockers: &[(&Locker, &str)]) -> Result<(), WriteError>
+    {
+        let mut serialised: Vec<(&str, String)> = Vec::new();
+
+        for (lkr, path) in lockers
+        {
+            serialised.push((path, lkr.serialise(path)?));
+        }
+
+        for (path, _) in &serialised
+        {
+            Locker::backup(path)?;
+        }
 
+        for (path, se) in &serialised
+        {
+            Locker::replace(path, se)?;
+        }
+
+        Ok(())
+    }
+
+    /*
+        As write, without backing up an existing file, e.g. git's 
+            temporary files
+    */
+    pub fn write_no_backup(&self, path: &str) -> Result<(), WriteError>
+    {
+        Locker::replace(path, &self.serialise(path)?)
+    }
+
+    fn serialise(&self, path: &str) -> Result<String, WriteError>
+    {
         let mut data: Vec<Entry> = Vec::new();
         let mut keys: Vec<Key> = Vec::new();
         let mut check_hash: Sha256 = Sha256::new();
 
+        if let Some(owner) = &self.owner
+        {
+            check_hash.update(owner);
+        }
+
+        if let Some(owner_key) = &self.owner_key
+        {
+            check_hash.update(&serde_json::to_vec(owner_key).unwrap());
+        }
+
+        if let Some(kdf) = &self.kdf
+        {
+            check_hash.update(&serde_json::to_vec(kdf).unwrap());
+        }
+
+        if let Some(recovery) = &self.recovery
+        {
+            check_hash.update(&serde_json::to_vec(recovery).unwrap());
+        }
+
+        if let Some(split) = &self.split
+        {
+            check_hash.update(&serde_json::to_vec(split).unwrap());
+        }
+
+        if let Some(rotating_to) = &self.rotating_to
+        {
+            check_hash.update(rotating_to);
+        }
+
         for (hash, value) in &self.data 
         {
             check_hash.update(hash);
-            check_hash.update(&value);
+            check_hash.update(value);
+
+            let metadata = match self.metadata.get(hash)
+            {
+                Some(m) => m.clone(),
+                None => Metadata::default()
+            };
+
+            if !metadata.is_empty()
+            {
+                check_hash.update(&serde_json::to_vec(&metadata).unwrap());
+            }
 
-            data.push(Entry { hash: hash.to_vec(), value: value.to_vec() });
+            data.push(Entry { hash: hash.to_vec(), value: value.to_vec(), metadata });
         }
 
         for key in &self.keys
@@ -340,27 +2226,52 @@ impl Locker
             check_hash.update(key);
         }
 
+        let mut recipients: Vec<RecipientKey> = Vec::new();
+
+        for public in &self.recipients
+        {
+            let recipient = RecipientKey { fingerprint: fingerprint(public).to_vec(), public_key: public.public_key_to_der().unwrap() };
+            check_hash.update(&recipient.fingerprint);
+            check_hash.update(&recipient.public_key);
+            recipients.push(recipient);
+        }
+
+        let mut groups: Vec<Group> = Vec::new();
+
+        for (name, members) in &self.groups
+        {
+            check_hash.update(name.as_bytes());
+            for member in members
+            {
+                check_hash.update(member);
+            }
+            groups.push(Group { name: name.clone(), members: members.iter().map(|m| Key { bytes: m.to_vec() }).collect() });
+        }
+
         let lkr = Lkr
         {
             version: program_version().to_string(), 
             check_hash: check_hash.finish().to_vec(), 
+            owner: self.owner.map(|o| o.to_vec()),
+            rotating_to: self.rotating_to.map(|r| r.to_vec()),
+            owner_key: self.owner_key.clone(),
+            kdf: self.kdf.clone(),
+            recovery: self.recovery.clone(),
+            split: self.split.clone(),
             entries: data,
-            keys: keys
+            keys,
+            recipients,
+            groups
         };
 
         match serde_json::to_string_pretty(&lkr)
         {
-            Ok(se) => 
-            {
-                write_file(path, se.as_bytes())
-            },
+            Ok(se) => Ok(se),
             Err(why) => 
             {
-                return Err(WriteError { why: format!("serde_json serialisation error: {}", why), file: path.to_string() })
+                Err(WriteError { why: format!("serde_json serialisation error: {}", why), file: path.to_string() })
             }
         }
-
-        Ok(())
     }
 }
 
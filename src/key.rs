@@ -0,0 +1,125 @@
+/*!
+
+    Keys a locker may be encrypted to
+
+    An RSA key wraps an envelope's data key with OAEP (and may
+        encrypt entries directly, see crypto::encrypt_to), an X25519
+        key wraps it with ECDH, HKDF-SHA256 and ChaCha20-Poly1305
+
+    Both are held as an openssl PKey, Recipient and Identity allow
+        an Rsa (e.g. from build_rsa) to be used in place of a PKey
+
+*/
+
+use openssl::
+{
+    rsa::Rsa,
+    pkey::{PKey, Id, Private, Public, HasPublic}
+};
+
+use crate::crypto::public_key;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyAlgorithm
+{
+    Rsa,
+    X25519
+}
+
+impl KeyAlgorithm
+{
+    pub fn parse(name: &str) -> Option<KeyAlgorithm>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "rsa" => Some(KeyAlgorithm::Rsa),
+            "x25519" => Some(KeyAlgorithm::X25519),
+            _ => None
+        }
+    }
+
+    pub fn of<T>(key: &PKey<T>) -> Option<KeyAlgorithm>
+    {
+        match key.id()
+        {
+            Id::RSA => Some(KeyAlgorithm::Rsa),
+            Id::X25519 => Some(KeyAlgorithm::X25519),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            KeyAlgorithm::Rsa => "RSA",
+            KeyAlgorithm::X25519 => "X25519"
+        }
+    }
+}
+
+/*
+    A key data may be encrypted to
+*/
+pub trait Recipient
+{
+    fn public(&self) -> PKey<Public>;
+}
+
+/*
+    A key data may be decrypted with, private() takes another 
+        reference to the same OpenSSL key, the key material is 
+        not copied
+*/
+pub trait Identity: Recipient
+{
+    fn private(&self) -> PKey<Private>;
+}
+
+impl<T: HasPublic> Recipient for Rsa<T>
+{
+    fn public(&self) -> PKey<Public>
+    {
+        PKey::from_rsa(public_key(self)).unwrap()
+    }
+}
+
+impl Identity for Rsa<Private>
+{
+    fn private(&self) -> PKey<Private>
+    {
+        PKey::from_rsa(self.clone()).unwrap()
+    }
+}
+
+impl<T: HasPublic> Recipient for PKey<T>
+{
+    fn public(&self) -> PKey<Public>
+    {
+        PKey::public_key_from_der(&self.public_key_to_der().unwrap()).unwrap()
+    }
+}
+
+impl Identity for PKey<Private>
+{
+    fn private(&self) -> PKey<Private>
+    {
+        self.clone()
+    }
+}
+
+impl<K: Recipient> Recipient for &K
+{
+    fn public(&self) -> PKey<Public>
+    {
+        (*self).public()
+    }
+}
+
+impl<K: Identity> Identity for &K
+{
+    fn private(&self) -> PKey<Private>
+    {
+        (*self).private()
+    }
+}
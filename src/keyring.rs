@@ -0,0 +1,228 @@
+/*!
+
+    A keyring is a directory of named keys, by default 
+        ~/.config/locker/keys (or $LOCKER_KEYRING)
+
+    Each key is stored as name.pem with its public key as
+        name.pub, so that a key can be matched to a locker's 
+        fingerprint without its passphrase. The file default
+        holds the name of the default key
+
+*/
+
+use std::path::{Path, PathBuf};
+
+use crate::
+{
+    crypto::{build_public, fingerprint},
+    util::{write_file, write_public_file, read_file_utf8, dump_bytes},
+    error::KeyringError,
+    key::Recipient
+};
+
+const DEFAULT_FILE: &str = "default";
+
+pub struct KeyringEntry
+{
+    pub name: String,
+    pub fingerprint: String,
+    pub default: bool
+}
+
+pub struct Keyring
+{
+    dir: PathBuf
+}
+
+impl Keyring
+{
+    pub fn new(dir: &str) -> Keyring
+    {
+        Keyring { dir: PathBuf::from(dir) }
+    }
+
+    /*
+        $LOCKER_KEYRING, or ~/.config/locker/keys
+    */
+    pub fn default_dir() -> String
+    {
+        if let Ok(dir) = std::env::var("LOCKER_KEYRING")
+        {
+            return dir
+        }
+
+        let home = match std::env::var("HOME")
+        {
+            Ok(h) => h,
+            Err(_) => std::env::var("USERPROFILE").unwrap_or(".".to_string())
+        };
+
+        Path::new(&home).join(".config").join("locker").join("keys").to_string_lossy().to_string()
+    }
+
+    fn pem_path(&self, name: &str) -> PathBuf
+    {
+        self.dir.join(format!("{}.pem", name))
+    }
+
+    fn pub_path(&self, name: &str) -> PathBuf
+    {
+        self.dir.join(format!("{}.pub", name))
+    }
+
+    fn names(&self) -> Vec<String>
+    {
+        let mut names: Vec<String> = Vec::new();
+
+        if let Ok(files) = std::fs::read_dir(&self.dir)
+        {
+            for file in files.flatten()
+            {
+                let path = file.path();
+                if path.extension().is_some_and(|e| e == "pem")
+                {
+                    if let Some(stem) = path.file_stem()
+                    {
+                        names.push(stem.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+
+        names.sort();
+        names
+    }
+
+    /*
+        Name, fingerprint and whether it is the default, for each key
+    */
+    pub fn list(&self) -> Vec<KeyringEntry>
+    {
+        let default = self.default_name();
+        let mut entries: Vec<KeyringEntry> = Vec::new();
+
+        for name in self.names()
+        {
+            let fp = match build_public(&self.pub_path(&name).to_string_lossy())
+            {
+                Ok(public) => dump_bytes(&fingerprint(&public)),
+                Err(_) => "(no public key)".to_string()
+            };
+
+            entries.push(KeyringEntry { default: default.as_ref() == Some(&name), name, fingerprint: fp });
+        }
+
+        entries
+    }
+
+    /*
+        Copy the PEM at pem into the keyring as name, along with
+            its public key
+    */
+    pub fn add<K: Recipient>(&self, name: &str, pem: &str, public: K) -> Result<(), KeyringError>
+    {
+        if name.is_empty() || name.contains(['/', '\\']) || name == DEFAULT_FILE
+        {
+            return Err(KeyringError { why: format!("invalid key name {}", name) });
+        }
+
+        if self.pem_path(name).exists()
+        {
+            return Err(KeyringError { why: format!("key {} is already in keyring {}", name, self.dir.display()) });
+        }
+
+        match std::fs::create_dir_all(&self.dir)
+        {
+            Ok(_) => {},
+            Err(why) => { return Err(KeyringError { why: format!("could not create {}: {}", self.dir.display(), why) }); }
+        }
+
+        let data = match read_file_utf8(pem)
+        {
+            Ok(d) => d,
+            Err(e) => { return Err(KeyringError { why: format!("{}", e) }); }
+        };
+
+        let public_pem = match public.public().public_key_to_pem()
+        {
+            Ok(p) => p,
+            Err(why) => { return Err(KeyringError { why: format!("could not encode public key: {}", why) }); }
+        };
+
+        write_file(&self.pem_path(name).to_string_lossy(), data.as_bytes());
+        write_public_file(&self.pub_path(name).to_string_lossy(), &public_pem);
+
+        Ok(())
+    }
+
+    pub fn remove(&self, name: &str) -> Result<(), KeyringError>
+    {
+        if !self.pem_path(name).exists()
+        {
+            return Err(KeyringError { why: format!("no key {} in keyring {}", name, self.dir.display()) });
+        }
+
+        for path in [self.pem_path(name), self.pub_path(name)]
+        {
+            if path.exists()
+            {
+                match std::fs::remove_file(&path)
+                {
+                    Ok(_) => {},
+                    Err(why) => { return Err(KeyringError { why: format!("could not remove {}: {}", path.display(), why) }); }
+                }
+            }
+        }
+
+        if self.default_name().as_deref() == Some(name)
+        {
+            let _ = std::fs::remove_file(self.dir.join(DEFAULT_FILE));
+        }
+
+        Ok(())
+    }
+
+    pub fn set_default(&self, name: &str) -> Result<(), KeyringError>
+    {
+        if !self.pem_path(name).exists()
+        {
+            return Err(KeyringError { why: format!("no key {} in keyring {}", name, self.dir.display()) });
+        }
+
+        write_file(&self.dir.join(DEFAULT_FILE).to_string_lossy(), name.as_bytes());
+        Ok(())
+    }
+
+    fn default_name(&self) -> Option<String>
+    {
+        match read_file_utf8(&self.dir.join(DEFAULT_FILE).to_string_lossy())
+        {
+            Ok(name) => Some(name.trim().to_string()),
+            Err(_) => None
+        }
+    }
+
+    /*
+        Path to the default key's PEM
+    */
+    pub fn default_key(&self) -> Option<String>
+    {
+        let name = self.default_name()?;
+        let path = self.pem_path(&name);
+        match path.exists()
+        {
+            true => Some(path.to_string_lossy().to_string()),
+            false => None
+        }
+    }
+
+    /*
+        Path to the PEM whose public key has one of fingerprints
+    */
+    pub fn find(&self, fingerprints: &[String]) -> Option<String>
+    {
+        self.list().into_iter()
+            .find(|e| fingerprints.iter().any(|fp| fp.eq_ignore_ascii_case(&e.fingerprint)))
+            .map(|e| self.pem_path(&e.name).to_string_lossy().to_string())
+    }
+}
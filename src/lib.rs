@@ -4,6 +4,11 @@ pub mod util;
 pub mod file;
 pub mod command;
 pub mod arguments;
+pub mod keyring;
+pub mod key;
+pub mod age;
+pub mod shamir;
+pub mod secret;
 
 const MAJOR: &str = env!("CARGO_PKG_VERSION_MAJOR");
 const MINOR: &str = env!("CARGO_PKG_VERSION_MINOR");
@@ -52,18 +57,9 @@ pub fn compatible(file_version: Version)
             true => {true},
             false => 
             {
-                if program.minor < file_version.minor
-                {
-                    false
-                }
-                else if file_version == initial_version && program != initial_version
-                {
-                    false
-                }
-                else
-                {
-                    true
-                }
+                // older releases cannot read newer minor versions, and
+                //  0.1.0 files are only read by 0.1.0
+                program.minor >= file_version.minor && !(file_version == initial_version && program != initial_version)
             }
         };
 
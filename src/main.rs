@@ -3,15 +3,18 @@ use std::path::Path;
 
 use locker::
 {
-    crypto::build_rsa,
+    crypto::{build_key, build_public, public_key_path, fingerprint, is_legacy_pem, min_key_bits, PassphraseKdf, prompt_new_passphrase},
+    key::Recipient,
+    util::warning,
+    secret::{SecretString, read_secret, disable_core_dumps},
     file::Locker,
     error::CommandResult, 
-    command::{extract_command, handle_command, handle_free_command},
-    arguments::{extract_arguments, extract_pass, extract_pem},
+    command::{extract_command, handle_command, handle_free_command, read_shares},
+    arguments::{extract_arguments, extract_pass, extract_pem_argument, find_pem, extract_pub, extract_group, extract_fields, extract_kdf, extract_shares},
     program_version
 };
 
-use rpassword;
+use openssl::pkey::{PKey, Private};
 
 const HELP_STRING: &str = r#"
 Locker is a lightweight encrypted key-value data store 
@@ -36,7 +39,10 @@ Locker general usage:
 
     Locker will automatically find a private key (RSA) as 
       a .pem file, and a lkr file as a .lkr in the current
-      directory (see options to specify paths)
+      directory (see options to specify paths). A key matching
+      the lkr file's fingerprint (in the keyring, or with a 
+      .pub in the current directory) is preferred, then the 
+      keyring's default key
 
     Options (see below) can be specified with - for options 
       without arguments, and -- for options with arguments
@@ -49,10 +55,32 @@ Locker general usage:
   
   Options:
   
-    --k pem          path to (encrypted) RSA private key in pem 
-                       format
+    --k pem          path to (encrypted) RSA or X25519 private
+                       key in pem format
 
-    --p pass         password for the pem file
+    --p pass         password for the pem file, or the
+                       passphrase of a passphrase locker
+
+    -passphrase      creates a locker with no PEM, its key
+                       is derived from a passphrase (scrypt).
+                       The kdf is stored in the lkr file,
+                       which is then always opened with 
+                       the passphrase
+
+    --kdf k          as -passphrase, with kdf scrypt (default)
+                       or pbkdf2 (PBKDF2-SHA256)
+
+    --pub key        path to a public key (.pub), values
+                       can be stored (or overwritten with -o)
+                       without the private key or passphrase
+
+    --field name=value
+                     sets a field of a structured entry 
+                       (a json object), repeatable. One value
+                       of - may be read from stdin, a name like
+                       conn.host sets a nested field. Other
+                       fields are kept. Retrieving entry.name
+                       gives one field, e.g. db.password
 
     -o               overwrite a key
 
@@ -62,26 +90,193 @@ Locker general usage:
 
     -show_keys       print all keys in .lkr file
 
+    --ls [pattern] [-regex] [--sort order] [-tree]
+                     print keys matching a glob pattern 
+                       (* any characters, ? one, [...] a set),
+                       or a regular expression with -regex,
+                       sorted by name or (where recorded) 
+                       modified, newest first. -tree prints
+                       keys as folders split at / or .
+
     --gen_key [pem]  generates an AES256 encrypted RSA
                        private key (with passphrase).
                        Writes to [pem] if specified or
-                       'locker.pem' if not
+                       'locker.pem' if not. The public key
+                       is written alongside as a .pub
 
     --re_key [pem]   generates a new AES256 encrypted RSA
-                       private key, and transfers data from 
-                       locker file to a new locker file 
-                       encrypted with the new key.
+                       private key, and re-encrypts each entry
+                       of the locker file with the new key.
                        Writes the key to [pem] if specified 
-                       or 'locker.pem' if not
+                       or 'locker.pem' if not. An interrupted
+                       re_key resumes if run again.
+                       With --to pem the locker is re-encrypted
+                       to an existing key instead
+
+    --passwd [pem]   changes the passphrase of a PEM, the
+                       key itself is unchanged so every lkr
+                       file encrypted to it stays valid. The
+                       new passphrase is asked for (twice),
+                       the current one may be given with --p
+
+    --upgrade_key [pem]
+                     rewrites a PEM using legacy OpenSSL 
+                       encryption as PKCS#8 (PBKDF2-SHA256,
+                       AES256), with the same passphrase
+
+    -force           allow --gen_key and --re_key to overwrite
+                       an existing PEM
+
+    --bits n         size of the RSA key generated by --gen_key
+                       or --re_key, one of 2048, 3072, 4096 
+                       (default) or 8192. Lockers encrypted to
+                       keys below LOCKER_MIN_KEY_BITS (default 
+                       3072) are warned about
+
+    --cipher c       cipher of a generated PEM, one of aes128,
+                       aes192 or aes256 (default)
+
+    --algorithm a    key type generated by --gen_key or 
+                       --re_key, rsa (default) or x25519. 
+                       X25519 keys may be used as the locker
+                       key or as recipients alongside RSA
     
+    --add_recipient pub  adds a recipient (RSA or X25519 
+                           public key), all data is 
+                           re-encrypted so that 
+                           each recipient's private key can 
+                           unlock the locker
+
+    --remove_recipient fingerprint [-mark_rotation]
+                         removes a recipient, all data is 
+                           re-encrypted with fresh keys for
+                           the remaining recipients. With 
+                           -mark_rotation every value is 
+                           flagged as needing rotation
+
+    -list_recipients     print the fingerprint of each 
+                           recipient
+
+    -add_recovery        escrows a one-time recovery code, 
+                           printed once. The code can unlock
+                           entries outside of groups if the
+                           key or its passphrase is lost
+
+    --recover [pem]      recovers the locker with its code 
+                           (asked for, or given with 
+                           --recovery_code code) to a new key,
+                           written to [pem] or 'locker.pem' (or
+                           an existing key with --to pem). The
+                           code and the old key are removed
+
+    -split --shares n --threshold m [--to pub,...]
+                         replaces the locker's key with a key 
+                           split into n Shamir shares, any m of
+                           which unlock it. The old key can no
+                           longer open it, a locker with other
+                           recipients or groups is refused. 
+                           Shares are written to 
+                           lkr.share1...lkr.sharen as text, or 
+                           with --to each encrypted to one of
+                           n public keys
+
+    --open_share file [pem]
+                         prints a share encrypted to pem
+
+    --shares a,b,...     unlock the locker with shares (text or
+                           share files) in place of a PEM
+
+    --add_group name fingerprint,...
+                         adds a named group of recipients
+
+    -list_groups         print each group and its members
+
+    --group name     stores the entry so that only members
+                       of the group can read it. Keys in
+                       other groups are hidden from 
+                       -show_keys
+
+    -keyring_list              print each key in the keyring
+                                 (~/.config/locker/keys, or 
+                                 $LOCKER_KEYRING) and its 
+                                 fingerprint
+
+    --keyring_add name pem     copies pem into the keyring as
+                                 name, with a .pub derived from
+                                 it (asks for its passphrase, 
+                                 or give it with --p)
+
+    --keyring_remove name      removes a key from the keyring
+
+    --keyring_default name     sets the default key
+
+    --git_merge base ours theirs
+                     a git merge driver, merges entries
+                       added, changed or deleted on either
+                       side into ours, a conflict if both
+                       changed the same entry
+
+    --git_diff lkr [-show_values]
+                     a git textconv, prints the keys of lkr
+                       with a digest of each value, or with
+                       -show_values the values
+
+    --diff a b [-show_values]
+                     prints keys only in lkr file a, only in
+                       b, and in both with different values
+
+    --merge a b [--strategy s] [--to out]
+                     adds keys only in b to a, or writes the
+                       merge to out. Different values are an
+                       error unless --strategy is prefer_a, 
+                       prefer_b, newest (by time modified, 
+                       where recorded) or interactive
+
+    --mv key new     renames entry key to new
+
+    --mv key --to lkr [--to_key name] [--to_pub pub]
+    --cp key --to lkr [--to_key name] [--to_pub pub]
+                     moves (or copies) an entry to lkr file,
+                       as name if given, encrypted to its key
+                       (found as for --k, or given with 
+                       --to_pub). The value is only decrypted
+                       in memory, a move writes both files
+                       only once both are ready, lkr first,
+                       so an interrupted move may leave the
+                       entry in both
+
     --import file    import data in JSON format
-                       from file
+                       from file, an age encrypted file is
+                       decrypted with --identity, the
+                       locker's X25519 key or --age_pass
 
     --export [file]  export data in JSON format.
                        if [file] is specified Locker
                        will output for [file], otherwise
                        data will be export to 'exported'
-                       in the current directory
+                       in the current directory. With 
+                       --to age1...[,age1...] or --age_pass
+                       the export is age encrypted. The 
+                       fields of structured entries are 
+                       exported (and imported) as json
+
+    --identity file  an age identity file (AGE-SECRET-KEY-1...)
+                       for --import
+
+    --age_pass pass  an age passphrase (scrypt) for --export
+                       or --import
+
+    -age_recipient   print the age recipient (age1...) of
+                       an X25519 locker key
+
+    --ns namespace   limits --ls and --export to keys in a 
+                       namespace (e.g. prod/db for 
+                       prod/db/password), exported without
+                       the namespace. --import adds it
+
+    --delete_ns namespace
+                     deletes every key in a namespace, after
+                       confirmation unless -force
 
 
 Notes:
@@ -89,12 +284,30 @@ Notes:
   Locker will always create a backup copy of the given .lkr file
     as a .lkr.bk, when data is written in any context.
 
+  Files Locker writes (PEMs, .lkr, .lkr.bk, exports) are readable 
+    by their owner only (0600). A PEM others can access is warned
+    about, or refused if LOCKER_STRICT_PERMISSIONS is set
+
+  To merge and diff .lkr files in git add to .gitattributes
+
+    *.lkr merge=locker diff=locker
+
+  and to .git/config (or ~/.gitconfig)
+
+    [merge "locker"]
+        name = locker entry merge
+        driver = locker --git_merge %O %A %B
+    [diff "locker"]
+        textconv = locker --git_diff
+
   By default if a key already exists Locker will not overwrite 
     its value. If you wish to re-write a key's value specify -o to 
     overwrite"#;
 
 fn main()
 {
+    disable_core_dumps();
+
     let mut args: Vec<String> = std::env::args().collect();
     let mut overwrite = false;
     let mut delete = false;
@@ -140,39 +353,47 @@ fn main()
         }
     };
 
-    match lkr_command.clone()
+    if let Some(command) = lkr_command.clone()
     {
-        Some(command) => 
+        match handle_free_command(command)
         {
-            match handle_free_command(command)
+            Ok(status) => 
             {
-                Ok(status) => 
+                match status 
                 {
-                    match status 
-                    {
-                        CommandResult::OK => {exit(0)},
-                        CommandResult::NothingToDo => {}
-                    }
-                }
-                Err(why) => 
-                {
-                    println!("{}", why); exit(1);
+                    CommandResult::OK => {exit(0)},
+                    CommandResult::NothingToDo => {}
                 }
             }
-        },
-        None => {}
+            Err(why) => 
+            {
+                println!("{}", why); exit(1);
+            }
+        }
+    }
+
+    let group: Option<String> = extract_group(&mut args);
+    let fields: Vec<String> = extract_fields(&mut args);
+
+    if let Some(public) = extract_pub(&mut args)
+    {
+        insert_public(public.as_str(), args, overwrite, group)
     }
 
-    let pem = match extract_pem(&mut args)
+    let new_kdf = match extract_kdf(&mut args)
     {
-        Ok(p) => p,
+        Ok(kdf) => kdf,
         Err(e) => 
         {
-            println!("Could not find PEM: {}", e);
-            std::process::exit(1);
+            println!("{}", e);
+            exit(1);
         }
     };
 
+    let shares = extract_shares(&mut args);
+
+    let pem_argument = extract_pem_argument(&mut args);
+
     let pass: Option<String> = extract_pass(&mut args);
 
     let (lkr_path, lkr_entry, lkr_data) = match extract_arguments(args)
@@ -185,6 +406,40 @@ fn main()
         }
     };
 
+    let lkr_path = match lkr_command.as_ref().and_then(|c| c.lkr_path())
+    {
+        Some(p) => Some(p),
+        None => lkr_path
+    };
+
+    let recovering = lkr_command.as_ref().is_some_and(|c| c.recovering());
+
+    // unlocked without the locker's PEM or passphrase
+    let keyless = recovering || shares.is_some();
+
+    let kdf = match keyless
+    {
+        true => None,
+        false => passphrase_kdf(lkr_path.as_deref(), new_kdf)
+    };
+
+    // a passphrase locker has no PEM
+    let pem = match (&kdf, pem_argument)
+    {
+        _ if keyless => None,
+        (Some(_), _) => None,
+        (None, Some(p)) => Some(p),
+        (None, None) => match find_pem(lkr_path.as_deref())
+        {
+            Ok(p) => Some(p),
+            Err(e) => 
+            {
+                println!("Could not find PEM: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+
     if lkr_command.is_none() && lkr_entry.is_none()
     {
         println!("No command or entry key provided, nothing to do");
@@ -199,33 +454,21 @@ fn main()
 
     let path = lkr_path.unwrap();
 
-    let password = match pass 
-    {
-        Some(s) => s,
-        None => 
-        {
-            rpassword::prompt_password
-            (
-                format!("Passphrase for PEM file {}: ",pem)
-            ).unwrap()
-        }
-    };
-        
-    let rsa = match build_rsa(pem.as_str(), &password.as_str())
+    let identity = match (recovering, shares, kdf, pem)
     {
-        Ok(v) => v,
-        Err(e) => 
-        {
-            println!("{}", e.why);
-            std::process::exit(1);
-        }
+        (true, _, _, _) => unlock_recovery(path.as_str(), lkr_command.as_ref().and_then(|c| c.recovery_code())),
+        (false, Some(shares), _, _) => unlock_shares(path.as_str(), &shares),
+        (false, None, Some(kdf), _) => unlock_passphrase(path.as_str(), kdf, pass, lkr_command.is_some() || lkr_data.is_some()),
+        (false, None, None, pem) => unlock_pem(pem.unwrap().as_str(), pass)
     };
 
+    verify_key(path.as_str(), &identity);
+
     match lkr_command
     {
         Some(command) =>
         {
-            match handle_command(path.as_str(), rsa, command)
+            match handle_command(path.as_str(), identity, command)
             {
                 Ok(status) => 
                 {
@@ -272,7 +515,7 @@ fn main()
                     }
                 }
 
-                match lkr.delete(&entry, rsa)
+                match lkr.delete(&entry, identity)
                 {
                     Ok(_) => (),
                     Err(e) => 
@@ -292,6 +535,10 @@ fn main()
                     }
                 }
             }
+            else if !fields.is_empty()
+            {
+                set_fields(path.as_str(), entry.as_str(), &fields, identity);
+            }
             else
             {
                 match lkr_data 
@@ -314,9 +561,9 @@ fn main()
                             }
                         }
             
-                        match lkr.get(entry.as_str(),rsa)
+                        match lkr.get_field(entry.as_str(),identity)
                         {
-                            Ok(value) => {println!("retrieved: {}", value);},
+                            Ok(value) => {println!("retrieved: {}", value.as_str());},
                             Err(why) => {println!("Key does not exist: {}", why); exit(0)}
                         }
                     },
@@ -336,7 +583,9 @@ fn main()
                             }
                         }
             
-                        match lkr.insert(entry.as_str(),&data,rsa, overwrite)
+                        lkr.claim_owner(&identity);
+
+                        match lkr.insert_in_group(entry.as_str(),&data,identity, overwrite, group.as_deref())
                         {
                             Ok(_) => {},
                             Err(why) => {println!("Key already exists {}", why); exit(0)}
@@ -359,6 +608,441 @@ fn main()
     
 }
 
+/*
+    Set name=value fields of entry, a value of - is read from stdin
+*/
+fn set_fields(path: &str, entry: &str, fields: &[String], identity: PKey<Private>)
+{
+    let mut lkr = Locker::new();
+
+    if Path::new(path).exists()
+    {
+        match lkr.read(path)
+        {
+            Ok(_) => {},
+            Err(why) => 
+            {
+                println!("{}", why);
+                exit(1);
+            }
+        }
+    }
+
+    if fields.iter().filter(|f| f.split_once('=').is_some_and(|(_, v)| v == "-")).count() > 1
+    {
+        println!("Only one --field may be read from stdin");
+        exit(1);
+    }
+
+    let mut values: Vec<(String, SecretString)> = Vec::new();
+
+    for field in fields
+    {
+        let (name, value) = match field.split_once('=')
+        {
+            Some((n, v)) if !n.is_empty() => (n, v),
+            _ => 
+            {
+                println!("--field expects name=value, got {}", field);
+                exit(1);
+            }
+        };
+
+        let value = match value
+        {
+            "-" =>
+            {
+                let mut input = match read_secret(&mut std::io::stdin())
+                {
+                    Ok(s) => s,
+                    Err(why) => 
+                    {
+                        println!("Could not read field {} from stdin: {}", name, why);
+                        exit(1);
+                    }
+                };
+                let length = input.trim_end_matches(['\n', '\r']).len();
+                input.truncate(length);
+                input
+            },
+            v => SecretString::new(v.to_string())
+        };
+
+        values.push((name.to_string(), value));
+    }
+
+    lkr.claim_owner(&identity);
+
+    match lkr.set_fields(entry, &values, &identity)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+
+    match lkr.write(path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+}
+
+fn unlock_pem(pem: &str, pass: Option<String>) -> PKey<Private>
+{
+    let password = SecretString::new(match pass 
+    {
+        Some(s) => s,
+        None => 
+        {
+            rpassword::prompt_password
+            (
+                format!("Passphrase for PEM file {}: ",pem)
+            ).unwrap()
+        }
+    });
+        
+    let identity = match build_key(pem, &password)
+    {
+        Ok(v) => v,
+        Err(e) => 
+        {
+            println!("{}", e.why);
+            std::process::exit(1);
+        }
+    };
+
+    if is_legacy_pem(pem)
+    {
+        warning(format!("{} uses legacy PEM encryption, upgrade it with --upgrade_key {}", pem, pem).as_str());
+    }
+
+    // a stale .pub would pick this PEM, or encrypt to another key
+    let public = public_key_path(pem);
+    if build_public(&public).is_ok_and(|p| fingerprint(&p) != fingerprint(&identity.public()))
+    {
+        warning(format!("{} does not match {}, it is out of date", public, pem).as_str());
+    }
+
+    identity
+}
+
+/*
+    The kdf of the passphrase locker at path, or kdf if a new 
+        passphrase locker is to be created there
+*/
+fn passphrase_kdf(path: Option<&str>, kdf: Option<PassphraseKdf>) -> Option<PassphraseKdf>
+{
+    let path = match path
+    {
+        Some(p) if Path::new(p).exists() => p,
+        _ => { return kdf }
+    };
+
+    let mut lkr: Locker = Locker::new();
+
+    match lkr.read(path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+
+    match (lkr.kdf(), kdf)
+    {
+        (Some(k), _) => Some(k),
+        (None, Some(_)) => 
+        {
+            println!("{} is not a passphrase locker, its key is a PEM", path);
+            exit(1);
+        },
+        (None, None) => None
+    }
+}
+
+/*
+    Derive the key of a passphrase locker, a new locker is written
+        (empty) if create is set so that commands find its kdf
+*/
+fn unlock_passphrase(path: &str, kdf: PassphraseKdf, pass: Option<String>, create: bool) -> PKey<Private>
+{
+    let exists = Path::new(path).exists();
+
+    let password = match (pass, exists)
+    {
+        (Some(s), _) => SecretString::new(s),
+        (None, true) => 
+        {
+            SecretString::new(rpassword::prompt_password
+            (
+                format!("Passphrase for lkr file {}: ", path)
+            ).unwrap())
+        },
+        // the passphrase is the only key to a new locker
+        (None, false) => match prompt_new_passphrase(&format!("Passphrase for new lkr file {}: ", path))
+        {
+            Some(p) => p,
+            None => 
+            {
+                println!("Passphrases do not match, {} was not created", path);
+                exit(1);
+            }
+        }
+    };
+
+    if exists
+    {
+        let mut lkr: Locker = Locker::new();
+
+        match lkr.read(path)
+        {
+            Ok(_) => {},
+            Err(why) => 
+            {
+                println!("{}", why);
+                exit(1);
+            }
+        }
+
+        return match lkr.unlock(&password)
+        {
+            Ok(identity) => identity,
+            Err(why) => 
+            {
+                println!("{}", why);
+                exit(1);
+            }
+        }
+    }
+
+    let (lkr, identity) = match Locker::with_passphrase(&password, kdf)
+    {
+        Ok(v) => v,
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    };
+
+    if create
+    {
+        match lkr.write(path)
+        {
+            Ok(_) => {},
+            Err(why) => 
+            {
+                println!("{}", why);
+                exit(1);
+            }
+        }
+    }
+
+    identity
+}
+
+/*
+    The key escrowed with a recovery code, prompted for unless given
+*/
+fn unlock_recovery(path: &str, code: Option<String>) -> PKey<Private>
+{
+    let code = SecretString::new(match code
+    {
+        Some(c) => c,
+        None => 
+        {
+            rpassword::prompt_password
+            (
+                format!("Recovery code for lkr file {}: ", path)
+            ).unwrap()
+        }
+    });
+
+    let mut lkr: Locker = Locker::new();
+
+    match lkr.read(path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+
+    match lkr.recovery_identity(&code)
+    {
+        Ok(identity) => identity,
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+}
+
+/*
+    The split key, from shares (or share files)
+*/
+fn unlock_shares(path: &str, shares: &[String]) -> PKey<Private>
+{
+    let mut lkr: Locker = Locker::new();
+
+    match lkr.read(path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+
+    let shares = match read_shares(shares)
+    {
+        Ok(s) => s,
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    };
+
+    match lkr.unlock_shares(&shares)
+    {
+        Ok(identity) => identity,
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+}
+
+/*
+    Compare the key's fingerprint with the one recorded in the lkr
+        file, before any decryption is attempted
+*/
+fn verify_key<K: Recipient>(path: &str, key: &K)
+{
+    if !Path::new(path).exists()
+    {
+        return
+    }
+
+    let mut lkr: Locker = Locker::new();
+
+    match lkr.read(path)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+
+    match lkr.verify_key(key)
+    {
+        Ok(_) => {},
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+
+    for (fp, bits) in lkr.weak_keys(min_key_bits())
+    {
+        warning(format!("{} is encrypted to a {} bit key {}, below the {} bit minimum, consider --re_key", path, bits, fp, min_key_bits()).as_str());
+    }
+}
+
+fn insert_public(public: &str, args: Vec<String>, overwrite: bool, group: Option<String>) -> !
+{
+    let (lkr_path, lkr_entry, lkr_data) = match extract_arguments(args)
+    {
+        Ok(args) => args,
+        Err(e) =>
+        {
+            println!("Command line arguments malformed: {}", e);
+            exit(1);
+        }
+    };
+
+    let (entry, data) = match (lkr_entry, lkr_data)
+    {
+        (Some(e), Some(d)) => (e, d),
+        _ => 
+        {
+            println!("An entry and data must be given when storing with a public key");
+            exit(1);
+        }
+    };
+
+    let path = match lkr_path
+    {
+        Some(p) => p,
+        None => 
+        {
+            println!("Could not find lkr file (in this directory, or in program arguments)");
+            exit(1);
+        }
+    };
+
+    let recipient = match build_public(public)
+    {
+        Ok(v) => v,
+        Err(e) => 
+        {
+            println!("{}", e.why);
+            exit(1);
+        }
+    };
+
+    verify_key(path.as_str(), &recipient);
+
+    let mut lkr: Locker = Locker::new();
+
+    if Path::new(path.as_str()).exists()
+    {
+        match lkr.read(path.as_str())
+        {
+            Ok(_) => {},
+            Err(why) => 
+            {
+                println!("{}", why);
+                exit(1);
+            }
+        }
+    }
+
+    match lkr.insert_in_group(entry.as_str(), &data, recipient, overwrite, group.as_deref())
+    {
+        Ok(_) => {},
+        Err(why) => {println!("Key already exists {}", why); exit(0)}
+    }
+
+    match lkr.write(path.as_str())
+    {
+        Ok(_) => {exit(0)},
+        Err(why) => 
+        {
+            println!("{}", why);
+            exit(1);
+        }
+    }
+}
+
 fn help()
 {
     println!("{}", HELP_STRING);
@@ -0,0 +1,373 @@
+/*!
+
+    Buffers holding secrets, decrypted entries, passphrases and
+        data keys
+
+    Their memory is locked (mlock, where permitted) so it is not
+        swapped out, and overwritten with zeros when dropped. They
+        are not Clone, copies must be made explicitly, and print
+        as [redacted] in Debug
+
+    Private keys are held by OpenSSL, which clears them when the
+        PKey is freed
+
+*/
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/*
+    Overwrite bytes with zeros, without the writes being optimised
+        away as dead stores
+*/
+pub fn wipe(bytes: &mut [u8])
+{
+    for b in bytes.iter_mut()
+    {
+        unsafe { std::ptr::write_volatile(b, 0); }
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/*
+    Lock (or unlock) the pages of a buffer in memory, best effort
+        as the RLIMIT_MEMLOCK may be small
+*/
+#[cfg(unix)]
+fn lock(ptr: *const u8, len: usize, locked: bool)
+{
+    if len == 0 { return; }
+    unsafe
+    {
+        match locked
+        {
+            true => { libc::mlock(ptr as *const libc::c_void, len); },
+            false => { libc::munlock(ptr as *const libc::c_void, len); }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn lock(_ptr: *const u8, _len: usize, _locked: bool) {}
+
+/*
+    Prevent the process writing a core dump (which would contain
+        any secret in memory) and, on Linux, being ptrace'd by
+        other processes of the same user
+*/
+pub fn disable_core_dumps()
+{
+    #[cfg(unix)]
+    unsafe
+    {
+        let limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        libc::setrlimit(libc::RLIMIT_CORE, &limit);
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe
+    {
+        libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0);
+    }
+}
+
+pub struct SecretBytes
+{
+    bytes: Vec<u8>
+}
+
+impl SecretBytes
+{
+    /*
+        Takes ownership of bytes without copying them, bytes should
+            not have been reallocated (e.g. grown by a push) since
+            a copy would be left behind
+    */
+    pub fn new(bytes: Vec<u8>) -> SecretBytes
+    {
+        lock(bytes.as_ptr(), bytes.capacity(), true);
+        SecretBytes { bytes }
+    }
+
+    pub fn as_bytes(&self) -> &[u8]
+    {
+        &self.bytes
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.bytes.is_empty()
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes
+{
+    fn from(bytes: Vec<u8>) -> SecretBytes
+    {
+        SecretBytes::new(bytes)
+    }
+}
+
+impl Deref for SecretBytes
+{
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8]
+    {
+        &self.bytes
+    }
+}
+
+impl DerefMut for SecretBytes
+{
+    fn deref_mut(&mut self) -> &mut [u8]
+    {
+        &mut self.bytes
+    }
+}
+
+impl Drop for SecretBytes
+{
+    fn drop(&mut self)
+    {
+        // the whole allocation, the capacity beyond len may hold
+        //  bytes from before a truncate
+        let capacity = self.bytes.capacity();
+        let ptr = self.bytes.as_mut_ptr();
+        for i in 0..capacity
+        {
+            unsafe { std::ptr::write_volatile(ptr.add(i), 0); }
+        }
+        compiler_fence(Ordering::SeqCst);
+        lock(ptr, capacity, false);
+    }
+}
+
+impl fmt::Debug for SecretBytes
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "[redacted]")
+    }
+}
+
+/*
+    A SecretBytes known to be UTF-8
+*/
+pub struct SecretString
+{
+    bytes: SecretBytes
+}
+
+impl SecretString
+{
+    pub fn new(s: String) -> SecretString
+    {
+        SecretString { bytes: SecretBytes::new(s.into_bytes()) }
+    }
+
+    pub fn as_str(&self) -> &str
+    {
+        // only constructed from a String
+        unsafe { std::str::from_utf8_unchecked(&self.bytes) }
+    }
+
+    /*
+        Shorten to len bytes, which must be on a char boundary. The 
+            bytes cut off are wiped when dropped
+    */
+    pub fn truncate(&mut self, len: usize)
+    {
+        assert!(self.as_str().is_char_boundary(len));
+        self.bytes.bytes.truncate(len);
+    }
+}
+
+/*
+    Read all of reader into a SecretString, e.g. a value piped to 
+        stdin. The buffer is grown by copying into a larger one, 
+        wiping the old, where a String would leave copies behind
+*/
+pub fn read_secret<R: Read>(reader: &mut R) -> io::Result<SecretString>
+{
+    let mut buffer = SecretBytes::new(vec![0; 1024]);
+    let mut len = 0;
+
+    loop
+    {
+        if len == buffer.len()
+        {
+            let mut grown = SecretBytes::new(vec![0; 2*buffer.len()]);
+            grown[..len].copy_from_slice(&buffer[..len]);
+            buffer = grown;
+        }
+
+        match reader.read(&mut buffer[len..])
+        {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => { return Err(e); }
+        }
+    }
+
+    buffer.bytes.truncate(len);
+
+    match std::str::from_utf8(&buffer)
+    {
+        Ok(_) => Ok(SecretString { bytes: buffer }),
+        Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+struct ByteCount(usize);
+
+impl Write for ByteCount
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+    {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()>
+    {
+        Ok(())
+    }
+}
+
+/*
+    value serialised as json, into a buffer of its exact length so 
+        it is never reallocated
+*/
+pub fn secret_json(value: &serde_json::Value) -> SecretString
+{
+    let mut count = ByteCount(0);
+    serde_json::to_writer(&mut count, value).unwrap();
+
+    let mut bytes = SecretBytes::new(Vec::with_capacity(count.0));
+    serde_json::to_writer(&mut bytes.bytes, value).unwrap();
+
+    // serde_json only writes UTF-8
+    SecretString { bytes }
+}
+
+/*
+    A json value holding secrets, e.g. the fields of a structured 
+        entry. Its strings are wiped when dropped, object keys 
+        (field names) are not
+*/
+pub struct SecretJson
+{
+    value: serde_json::Value
+}
+
+impl SecretJson
+{
+    pub fn new(value: serde_json::Value) -> SecretJson
+    {
+        SecretJson { value }
+    }
+}
+
+fn wipe_json(value: &mut serde_json::Value)
+{
+    match value
+    {
+        serde_json::Value::String(s) => unsafe { wipe(s.as_bytes_mut()) },
+        serde_json::Value::Array(a) => a.iter_mut().for_each(wipe_json),
+        serde_json::Value::Object(o) => o.values_mut().for_each(wipe_json),
+        _ => {}
+    }
+}
+
+impl Deref for SecretJson
+{
+    type Target = serde_json::Value;
+
+    fn deref(&self) -> &serde_json::Value
+    {
+        &self.value
+    }
+}
+
+impl DerefMut for SecretJson
+{
+    fn deref_mut(&mut self) -> &mut serde_json::Value
+    {
+        &mut self.value
+    }
+}
+
+impl Drop for SecretJson
+{
+    fn drop(&mut self)
+    {
+        wipe_json(&mut self.value);
+    }
+}
+
+impl fmt::Debug for SecretJson
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "[redacted]")
+    }
+}
+
+impl From<String> for SecretString
+{
+    fn from(s: String) -> SecretString
+    {
+        SecretString::new(s)
+    }
+}
+
+impl Deref for SecretString
+{
+    type Target = str;
+
+    fn deref(&self) -> &str
+    {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for SecretString
+{
+    fn eq(&self, other: &str) -> bool
+    {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SecretString
+{
+    fn eq(&self, other: &&str) -> bool
+    {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for SecretString
+{
+    fn eq(&self, other: &String) -> bool
+    {
+        self.as_str() == other
+    }
+}
+
+impl fmt::Debug for SecretString
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "[redacted]")
+    }
+}
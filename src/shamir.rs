@@ -0,0 +1,173 @@
+/*!
+
+    Shamir secret sharing over GF(256), so that any threshold of
+        shares reconstruct a secret and fewer reveal nothing of it
+
+    Each byte of the secret is the constant term of a random
+        polynomial of degree threshold-1, share x holds its value
+        at x (1..=255) for every byte
+
+    A share is written as text
+
+    LOCKER-SHARE-<x>-<threshold>-<hex>
+
+*/
+
+use openssl::rand::rand_bytes;
+
+use crate::
+{
+    error::ShamirError,
+    util::{dump_bytes, read_bytes}
+};
+
+const SHARE_PREFIX: &str = "LOCKER-SHARE";
+
+/*
+    Multiplication in GF(256) (AES polynomial x^8+x^4+x^3+x+1),
+        without branching on the operands
+*/
+fn mul(mut a: u8, mut b: u8) -> u8
+{
+    let mut p: u8 = 0;
+    for _ in 0..8
+    {
+        p ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    p
+}
+
+// a^254 = a^-1
+fn inverse(a: u8) -> u8
+{
+    let mut result: u8 = 1;
+    let mut base = a;
+    let mut e: u8 = 254;
+    while e > 0
+    {
+        if e & 1 == 1 { result = mul(result, base); }
+        base = mul(base, base);
+        e >>= 1;
+    }
+    result
+}
+
+/*
+    Split secret into shares (x, bytes), any threshold of which
+        reconstruct it
+*/
+pub fn split(secret: &[u8], shares: u8, threshold: u8) -> Result<Vec<(u8, Vec<u8>)>, ShamirError>
+{
+    if threshold < 2 || shares < threshold
+    {
+        return Err(ShamirError { why: format!("need 2 <= threshold <= shares, got threshold {} of {} shares", threshold, shares) });
+    }
+
+    let degree = threshold as usize-1;
+    let mut coefficients = vec![0; secret.len()*degree];
+    rand_bytes(&mut coefficients).unwrap();
+
+    let mut out: Vec<(u8, Vec<u8>)> = Vec::new();
+
+    for x in 1..=shares
+    {
+        let mut bytes: Vec<u8> = Vec::new();
+
+        for (s, c) in secret.iter().zip(coefficients.chunks(degree))
+        {
+            // Horner's method, from the highest degree down to the secret
+            let y = c.iter().rev().fold(0u8, |acc, a| mul(acc, x) ^ a);
+            bytes.push(mul(y, x) ^ s);
+        }
+
+        out.push((x, bytes));
+    }
+
+    Ok(out)
+}
+
+/*
+    Reconstruct a secret from (at least threshold) shares by
+        Lagrange interpolation at 0
+*/
+pub fn combine(shares: &[(u8, Vec<u8>)]) -> Result<Vec<u8>, ShamirError>
+{
+    if shares.is_empty()
+    {
+        return Err(ShamirError { why: "no shares given".to_string() });
+    }
+
+    let length = shares[0].1.len();
+
+    for (i, (x, bytes)) in shares.iter().enumerate()
+    {
+        if *x == 0 || bytes.len() != length
+        {
+            return Err(ShamirError { why: format!("malformed share {}", x) });
+        }
+
+        if shares[0..i].iter().any(|(other, _)| other == x)
+        {
+            return Err(ShamirError { why: format!("share {} given more than once", x) });
+        }
+    }
+
+    let mut secret = vec![0u8; length];
+
+    for (i, (xi, yi)) in shares.iter().enumerate()
+    {
+        // l_i(0) = prod_{j != i} x_j / (x_j - x_i), subtraction is xor
+        let mut basis: u8 = 1;
+        for (j, (xj, _)) in shares.iter().enumerate()
+        {
+            if i != j
+            {
+                basis = mul(basis, mul(*xj, inverse(xj ^ xi)));
+            }
+        }
+
+        for (s, y) in secret.iter_mut().zip(yi.iter())
+        {
+            *s ^= mul(basis, *y);
+        }
+    }
+
+    Ok(secret)
+}
+
+pub fn share_string(x: u8, threshold: u8, bytes: &[u8]) -> String
+{
+    format!("{}-{}-{}-{}", SHARE_PREFIX, x, threshold, dump_bytes(bytes))
+}
+
+/*
+    The x, threshold and bytes of a share written by share_string
+*/
+pub fn parse_share(share: &str) -> Result<(u8, u8, Vec<u8>), ShamirError>
+{
+    let malformed = || ShamirError { why: format!("malformed share {}", share) };
+
+    let rest = match share.trim().strip_prefix(SHARE_PREFIX).and_then(|r| r.strip_prefix('-'))
+    {
+        Some(r) => r,
+        None => { return Err(malformed()); }
+    };
+
+    let parts: Vec<&str> = rest.split('-').collect();
+
+    match parts[..]
+    {
+        [x, threshold, hex] if !hex.is_empty() && hex.len() % 2 == 0 && hex.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            match (x.parse::<u8>(), threshold.parse::<u8>())
+            {
+                (Ok(x), Ok(t)) if x > 0 => Ok((x, t, read_bytes(hex.to_string()))),
+                _ => Err(malformed())
+            }
+        },
+        _ => Err(malformed())
+    }
+}
@@ -1,5 +1,5 @@
 use std::path::Path;
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::fmt::Write as fmtWrite;
 use std::io::Write as ioWrite;
 use std::io::Read;
@@ -52,10 +52,99 @@ pub fn read_file_utf8(path: &str) -> Result<String, ReadFileError>
 
 }
 
+const PRIVATE_MODE: u32 = 0o600;
+const PUBLIC_MODE: u32 = 0o644;
+
+/*
+    Files are written readable by their owner only (0600), an 
+        existing file is restricted before any data is written
+*/
 pub fn write_file(path: &str, data: &[u8])
 {
-    let mut file = File::create(path).unwrap();
-    file.write_all(data).unwrap();
+    write_file_mode(path, data, PRIVATE_MODE).unwrap();
+}
+
+/*
+    As write_file, returning the error, e.g. for a locker written 
+        in place of another
+*/
+pub fn try_write_file(path: &str, data: &[u8]) -> std::io::Result<()>
+{
+    write_file_mode(path, data, PRIVATE_MODE)
+}
+
+/*
+    For files anyone may read, public keys (.pub)
+*/
+pub fn write_public_file(path: &str, data: &[u8])
+{
+    write_file_mode(path, data, PUBLIC_MODE).unwrap();
+}
+
+fn write_file_mode(path: &str, data: &[u8], mode: u32) -> std::io::Result<()>
+{
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(mode);
+    }
+
+    let mut file = options.open(path)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+    }
+
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    file.write_all(data)
+}
+
+/*
+    Restrict an existing file to its owner (0600)
+*/
+pub fn restrict_permissions(path: &str) -> std::io::Result<()>
+{
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(PRIVATE_MODE))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(())
+    }
+}
+
+/*
+    The mode of a file if its group or others have any access 
+        to it, as ssh checks private keys
+*/
+pub fn accessible_by_others(path: &str) -> Option<u32>
+{
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        match std::fs::metadata(path)
+        {
+            Ok(m) if m.permissions().mode() & 0o077 != 0 => Some(m.permissions().mode() & 0o777),
+            _ => None
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
 }
 
 pub fn read_file_raw(path: &str) -> Result<Vec<u8>, ReadFileError>
@@ -102,6 +191,40 @@ pub fn warning(msg: &str)
     println!("[WARNING] {}", msg);
 }
 
+/*
+    A regular expression matching the whole of strings matched by 
+        glob, where * is any characters, ? any one character and 
+        [...] (or [!...]) a set of characters
+*/
+pub fn glob_regex(glob: &str) -> String
+{
+    let mut re = String::from("^");
+    let mut in_set = false;
+
+    for c in glob.chars()
+    {
+        match (c, in_set)
+        {
+            (']', true) => { in_set = false; re.push(']'); },
+            ('\\', true) => { re.push_str("\\\\"); },
+            (_, true) => { re.push(c); },
+            ('*', false) => { re.push_str(".*"); },
+            ('?', false) => { re.push('.'); },
+            ('[', false) => { in_set = true; re.push('['); },
+            (_, false) => { re.push_str(&regex::escape(&c.to_string())); }
+        }
+
+        if in_set && re.ends_with("[!")
+        {
+            re.pop();
+            re.push('^');
+        }
+    }
+
+    re.push('$');
+    re
+}
+
 pub fn find_file_in_dir(pattern: Regex) -> Result<String, NoSuchFileError>
 {
     match std::fs::read_dir(".")
@@ -138,11 +261,34 @@ pub fn find_file_in_dir(pattern: Regex) -> Result<String, NoSuchFileError>
     }
 }
 
+/*
+    All files in the current directory matching pattern, sorted
+*/
+pub fn find_files_in_dir(pattern: Regex) -> Vec<String>
+{
+    let mut found: Vec<String> = Vec::new();
+
+    if let Ok(files) = std::fs::read_dir(".")
+    {
+        for file in files.flatten()
+        {
+            match file.file_name().to_str()
+            {
+                Some(name) if pattern.is_match(name) => found.push(name.to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    found.sort();
+    found
+}
+
 pub fn compress(bytes: &[u8]) -> Result<Vec<u8>, CompressionError>
 {
     let mut encoder = Encoder::new(Vec::new());
     
-    match encoder.write_all(&bytes)
+    match encoder.write_all(bytes)
     {
         Ok(_) => (),
         Err(e) => 
@@ -198,4 +344,24 @@ pub fn from_base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>
         general_purpose::STANDARD_NO_PAD.decode(s)
             .map_err(|e| de::Error::custom(format!("invalid base64 string: {}, {}", s, e)))
     })
+}
+
+pub fn as_base64_option<S: Serializer>(val: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+    match val {
+        Some(v) => serializer.serialize_some(&general_purpose::STANDARD_NO_PAD.encode(v)),
+        None => serializer.serialize_none()
+    }
+}
+
+pub fn from_base64_option<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error> {
+    use serde::de;
+
+    <Option<&str>>::deserialize(deserializer).and_then(|s| {
+        match s {
+            Some(s) => general_purpose::STANDARD_NO_PAD.decode(s)
+                .map(Some)
+                .map_err(|e| de::Error::custom(format!("invalid base64 string: {}, {}", s, e))),
+            None => Ok(None)
+        }
+    })
 }
\ No newline at end of file
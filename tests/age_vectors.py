@@ -0,0 +1,117 @@
+#!/usr/bin/env python3
+"""
+    Writes tests/encrypted.age and tests/encrypted_scrypt.age with an age
+        v1 encoder written from the specification (age-encryption.org/v1)
+        on Python's cryptography package, independent of src/age.rs, so
+        decrypt_known_files is not a round trip through the same code
+
+    python3 tests/age_vectors.py
+"""
+
+import base64
+import hashlib
+import hmac
+import os
+
+from cryptography.hazmat.primitives.asymmetric.x25519 import X25519PrivateKey
+from cryptography.hazmat.primitives.ciphers.aead import ChaCha20Poly1305
+from cryptography.hazmat.primitives.hashes import SHA256
+from cryptography.hazmat.primitives.kdf.hkdf import HKDF
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+PLAIN = b"abc"
+PASSPHRASE = b"password"
+SCRYPT_WORK_FACTOR = 10
+CHUNK = 64 * 1024
+
+BECH32 = "qpzry9x8gf2tvdw0s3jn54khce6mua7l"
+
+
+def bech32_decode(s):
+    s = s.lower()
+    hrp, data = s[:s.rindex("1")], [BECH32.index(c) for c in s[s.rindex("1") + 1:]]
+
+    values = [ord(c) >> 5 for c in hrp] + [0] + [ord(c) & 31 for c in hrp] + data
+    chk = 1
+    for v in values:
+        top = chk >> 25
+        chk = (chk & 0x1ffffff) << 5 ^ v
+        for i, g in enumerate([0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3]):
+            chk ^= g if (top >> i) & 1 else 0
+    assert chk == 1, "bad bech32 checksum"
+
+    acc, bits, out = 0, 0, []
+    for v in data[:-6]:
+        acc, bits = acc << 5 | v, bits + 5
+        if bits >= 8:
+            bits -= 8
+            out.append(acc >> bits & 0xff)
+    return hrp, bytes(out)
+
+
+def b64(data):
+    return base64.b64encode(data).decode().rstrip("=")
+
+
+def hkdf(ikm, salt, info):
+    return HKDF(SHA256(), 32, salt or None, info).derive(ikm)
+
+
+def wrap(key, file_key):
+    return ChaCha20Poly1305(key).encrypt(bytes(12), file_key, None)
+
+
+def stanza(kind, args, body):
+    encoded = b64(body)
+    lines = [encoded[i:i + 64] for i in range(0, len(encoded), 64)]
+    if len(encoded) % 64 == 0:
+        lines.append("")
+    return "-> " + " ".join([kind] + args) + "\n" + "\n".join(lines) + "\n"
+
+
+def x25519_stanza(recipient, file_key):
+    ephemeral = X25519PrivateKey.generate()
+    ephemeral_public = ephemeral.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw)
+    recipient_public = recipient.public_bytes(Encoding.Raw, PublicFormat.Raw)
+    key = hkdf(ephemeral.exchange(recipient), ephemeral_public + recipient_public, b"age-encryption.org/v1/X25519")
+    return stanza("X25519", [b64(ephemeral_public)], wrap(key, file_key))
+
+
+def scrypt_stanza(passphrase, file_key):
+    salt = os.urandom(16)
+    n = 1 << SCRYPT_WORK_FACTOR
+    key = hashlib.scrypt(passphrase, salt=b"age-encryption.org/v1/scrypt" + salt, n=n, r=8, p=1, dklen=32)
+    return stanza("scrypt", [b64(salt), str(SCRYPT_WORK_FACTOR)], wrap(key, file_key))
+
+
+def age_file(stanza_for, plain):
+    file_key = os.urandom(16)
+    header = "age-encryption.org/v1\n" + stanza_for(file_key) + "---"
+    mac = hmac.new(hkdf(file_key, b"", b"header"), header.encode(), hashlib.sha256).digest()
+
+    nonce = os.urandom(16)
+    payload = ChaCha20Poly1305(hkdf(file_key, nonce, b"payload"))
+    chunks = [plain[i:i + CHUNK] for i in range(0, len(plain), CHUNK)] or [b""]
+    body = b"".join(
+        payload.encrypt(i.to_bytes(11, "big") + bytes([i == len(chunks) - 1]), chunk, None)
+        for i, chunk in enumerate(chunks)
+    )
+
+    return (header + " " + b64(mac) + "\n").encode() + nonce + body
+
+
+def main():
+    identity = open("tests/donotuse_age.txt").read().split()[0]
+    hrp, secret = bech32_decode(identity)
+    assert hrp == "age-secret-key-"
+    recipient = X25519PrivateKey.from_private_bytes(secret).public_key()
+
+    with open("tests/encrypted.age", "wb") as f:
+        f.write(age_file(lambda file_key: x25519_stanza(recipient, file_key), PLAIN))
+
+    with open("tests/encrypted_scrypt.age", "wb") as f:
+        f.write(age_file(lambda file_key: scrypt_stanza(PASSPHRASE, file_key), PLAIN))
+
+
+if __name__ == "__main__":
+    main()
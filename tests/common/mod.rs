@@ -1,5 +1,9 @@
+#![allow(dead_code)]
+
 pub const TEST_STRING: &str = "a secret message";
 pub const PEM_PATH: &str = "tests/donotuse.pem";
+pub const PUB_PATH: &str = "tests/donotuse.pub";
+pub const LEGACY_PEM_PATH: &str = "tests/donotuse_legacy.pem";
 pub const PEM_PASSWORD: &str = "password";
 pub const TEST_ENCRYPTED_FILE: &str = "tests/encrypted";
 pub const TEST_ENCRYPTED_FILE_PLAIN: &str = "abc";
\ No newline at end of file
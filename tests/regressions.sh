@@ -41,6 +41,10 @@ repeat(){
 
 declare -a FAILING
 
+# git checks keys out readable by others, which locker warns about
+chmod 600 tests/*.pem
+
+
 STATUS=0
 for reg in $(ls tests/regressions); 
 do 
@@ -56,7 +60,7 @@ do
         echo -e "\n${GREEN}PASSED${NC}"
     fi
 
-    for f in reg.* *.pem *.lkr exported data; do 
+    for f in reg.* *.pem *.pub *.lkr exported data; do 
       if [ -f $f ]; then
         rm $f
       fi
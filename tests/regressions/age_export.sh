@@ -0,0 +1,17 @@
+#!/bin/bash
+echo -e "age encrypted export and import:\n"
+locker --gen_key reg.pem --p password --algorithm x25519
+locker this_is_a_key this_is_a_secret_value --f reg.lkr --k reg.pem --p password
+locker -age_recipient --f reg.lkr --k reg.pem --p password > reg.recipient
+grep age1 reg.recipient
+locker --export reg.age --to $(cat reg.recipient) --f reg.lkr --k reg.pem --p password
+head -1 reg.age | grep "age-encryption.org/v1"
+grep -v this_is_a_secret_value reg.age > /dev/null
+locker --import reg.age --f reg.import.lkr --k reg.pem --p password
+locker this_is_a_key --f reg.import.lkr --k reg.pem --p password | grep this_is_a_secret_value
+locker --import tests/encrypted.age --identity tests/donotuse_age.txt --f reg.import.lkr --k reg.pem --p password 2>&1 | grep "Could not parse\|json\|JSON"
+locker --export reg.scrypt.age --age_pass hunter2 --f reg.lkr --k reg.pem --p password
+head -2 reg.scrypt.age | grep "scrypt"
+rm reg.import.lkr
+locker --import reg.scrypt.age --age_pass hunter2 --f reg.import.lkr --k reg.pem --p password
+locker this_is_a_key --f reg.import.lkr --k reg.pem --p password | grep this_is_a_secret_value
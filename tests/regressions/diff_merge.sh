@@ -0,0 +1,16 @@
+#!/bin/bash
+echo -e "diff and merge two lockers:\n"
+cp tests/test.lkr reg.a.lkr
+cp tests/test.lkr reg.b.lkr
+locker only_a abc --k tests/donotuse.pem --f reg.a.lkr --p password
+locker only_b def --k tests/donotuse.pem --f reg.b.lkr --p password
+locker this_is_a_key ghi -o --k tests/donotuse.pem --f reg.b.lkr --p password
+locker --diff reg.a.lkr reg.b.lkr -show_values --k tests/donotuse.pem --p password
+locker --diff reg.a.lkr reg.b.lkr --k tests/donotuse.pem --p password | grep -A1 "only in reg.b.lkr" | grep only_b
+locker --merge reg.a.lkr reg.b.lkr --to reg.out.lkr --k tests/donotuse.pem --p password | grep "keys differ"
+locker --merge reg.a.lkr reg.b.lkr --strategy newest --to reg.out.lkr --k tests/donotuse.pem --p password | grep "has no modified time"
+locker --merge reg.a.lkr reg.b.lkr --strategy prefer_b --to reg.out.lkr --k tests/donotuse.pem --p password
+locker only_b --k tests/donotuse.pem --f reg.out.lkr --p password | grep def
+echo b | locker --merge reg.a.lkr reg.b.lkr --strategy interactive --k tests/donotuse.pem --p password
+locker only_a --k tests/donotuse.pem --f reg.a.lkr --p password | grep abc
+locker this_is_a_key --k tests/donotuse.pem --f reg.a.lkr --p password | grep ghi
@@ -0,0 +1,14 @@
+#!/bin/bash
+echo -e "structured entries:\n"
+cp tests/test.lkr reg.lkr
+echo "s3cret" | locker db --field user=admin --field password=- --k tests/donotuse.pem --f reg.lkr --p password
+locker db --field port=5432 --k tests/donotuse.pem --f reg.lkr --p password
+locker db.user --k tests/donotuse.pem --f reg.lkr --p password | grep admin
+locker this_is_a_key --field a=b --k tests/donotuse.pem --f reg.lkr --p password | grep "holds a value"
+locker --export reg.export --k tests/donotuse.pem --f reg.lkr --p password
+grep '"port": "5432"' reg.export
+rm reg.lkr
+locker --import reg.export --k tests/donotuse.pem --f reg.lkr --p password
+locker --import reg.export --k tests/donotuse.pem --f reg.lkr --p password 2>&1 | grep "Could not import" && \
+echo "x" | locker db2 --field a=- --field b=- --k tests/donotuse.pem --f reg.lkr --p password | grep "Only one --field" && \
+locker db.password --k tests/donotuse.pem --f reg.lkr --p password | grep "retrieved: s3cret$"
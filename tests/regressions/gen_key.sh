@@ -2,4 +2,6 @@
 echo -e "gen_key:\n"
 locker --gen_key gen_key.pem --p password
 locker this_is_a_key this_is_a_secret_value --f gen.lkr --k gen_key.pem --p password
-locker -show_keys --f gen.lkr --k gen_key.pem --p password | grep this_is_a_key
\ No newline at end of file
+locker -show_keys --f gen.lkr --k gen_key.pem --p password | grep this_is_a_key
+locker this_is_a_public_key abc --f gen.lkr --pub gen_key.pub
+locker this_is_a_public_key --f gen.lkr --k gen_key.pem --p password | grep abc
@@ -0,0 +1,16 @@
+#!/bin/bash
+echo -e "git merge driver and textconv diff:\n"
+cp tests/test.lkr reg.base.lkr
+cp tests/test.lkr reg.ours.lkr
+cp tests/test.lkr reg.theirs.lkr
+locker ours_key ours_value --k tests/donotuse.pem --f reg.ours.lkr --p password
+locker theirs_key theirs_value --k tests/donotuse.pem --f reg.theirs.lkr --p password
+locker --git_merge reg.base.lkr reg.ours.lkr reg.theirs.lkr
+locker --git_diff reg.ours.lkr --k tests/donotuse.pem --p password | grep theirs_key
+locker --git_diff reg.ours.lkr -show_values --k tests/donotuse.pem --p password | grep "ours_key = ours_value"
+cp reg.ours.lkr reg.base.lkr
+cp reg.ours.lkr reg.theirs.lkr
+locker this_is_a_key abc -o --k tests/donotuse.pem --f reg.theirs.lkr --p password
+locker this_is_a_key def -o --k tests/donotuse.pem --f reg.ours.lkr --p password
+locker --git_merge reg.base.lkr reg.ours.lkr reg.theirs.lkr | grep "changed on both sides"
+locker theirs_key --k tests/donotuse.pem --f reg.ours.lkr --p password | grep theirs_value
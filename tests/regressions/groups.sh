@@ -0,0 +1,10 @@
+#!/bin/bash
+echo -e "group entries hidden from non-members, k, f, p specified:\n"
+cp tests/test.lkr reg.lkr
+locker --gen_key recipient.pem --p other_password
+locker --add_recipient recipient.pub --k tests/donotuse.pem --f reg.lkr --p password
+FINGERPRINT=$(locker -list_recipients --k tests/donotuse.pem --f reg.lkr --p password | grep -v "(this key)")
+locker --add_group ops $FINGERPRINT --k tests/donotuse.pem --f reg.lkr --p password
+locker this_is_a_prod_key abc --group ops --k tests/donotuse.pem --f reg.lkr --p password
+locker -show_keys --k tests/donotuse.pem --f reg.lkr --p password | grep "1 keys not readable"
+locker this_is_a_prod_key --k recipient.pem --f reg.lkr --p other_password | grep "abc"
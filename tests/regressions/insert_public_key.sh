@@ -0,0 +1,5 @@
+#!/bin/bash
+echo -e "set new key with public key only, pub, f specified:\n"
+cp tests/test.lkr reg.lkr
+locker this_is_a_new_key abc --pub tests/donotuse.pub --f reg.lkr
+locker this_is_a_new_key --k tests/donotuse.pem --f reg.lkr --p password | grep "abc"
@@ -0,0 +1,7 @@
+#!/bin/bash
+echo -e "a public key does not take over an older locker:\n"
+cp tests/test.lkr reg.lkr
+locker --gen_key reg.other.pem --p other
+locker stranger abc --pub reg.other.pub --f reg.lkr
+grep '"owner"' reg.lkr && exit 1
+locker this_is_a_key --k tests/donotuse.pem --f reg.lkr --p password | grep this_is_a_secret_value
@@ -0,0 +1,8 @@
+#!/bin/bash
+echo -e "gen_key with --bits and --cipher, small keys are warned about:\n"
+locker --gen_key small.pem --p password --bits 2048 --cipher aes128
+locker this_is_a_key this_is_a_secret_value --f small.lkr --k small.pem --p password
+grep '"bits": 2048' small.lkr
+locker this_is_a_key --f small.lkr --k small.pem --p password | grep "2048 bit key"
+LOCKER_MIN_KEY_BITS=2048 locker this_is_a_key --f small.lkr --k small.pem --p password | grep -v "2048 bit key" | grep this_is_a_secret_value
+locker --gen_key bad.pem --p password --bits 1000 | grep "unsupported key size"
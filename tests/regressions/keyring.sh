@@ -0,0 +1,18 @@
+#!/bin/bash
+echo -e "keyring key selected by fingerprint, p specified:\n"
+export LOCKER_KEYRING=reg.keyring
+cp tests/test.lkr reg.lkr
+locker this_is_a_new_key abc --k tests/donotuse.pem --f reg.lkr --p password
+locker --gen_key other.pem --p other_password
+cp tests/donotuse.pub other.pub
+locker --keyring_add other other.pem --p other_password
+locker --keyring_add donotuse tests/donotuse.pem --p password
+locker --keyring_default other
+locker -keyring_list | grep "other .* (default)"
+locker -keyring_list | grep "^other" > reg.other
+rm other.pem
+locker this_is_a_new_key --f reg.lkr --p password | grep "abc" && ! grep -q 16B94CE1646E00C655D62AABA6BC96B1E409A53529F3235E8C72267671D130CB reg.other
+status=$?
+rm -r reg.keyring
+unset LOCKER_KEYRING
+[ $status = 0 ]
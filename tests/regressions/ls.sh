@@ -0,0 +1,11 @@
+#!/bin/bash
+echo -e "list keys:\n"
+cp tests/test.lkr reg.lkr
+locker db.user abc --k tests/donotuse.pem --f reg.lkr --p password
+locker db/password def --k tests/donotuse.pem --f reg.lkr --p password
+locker --ls --k tests/donotuse.pem --f reg.lkr --p password
+locker --ls "db*" --k tests/donotuse.pem --f reg.lkr --p password | grep this_is_a_key && exit 1
+locker --ls "^db\.u" -regex --k tests/donotuse.pem --f reg.lkr --p password | grep db/password && exit 1
+locker --ls --sort size --k tests/donotuse.pem --f reg.lkr --p password | grep "unknown sort"
+locker --ls --sort modified --k tests/donotuse.pem --f reg.lkr --p password | tail -1 | grep this_is_a_key
+locker --ls "db*" -tree --k tests/donotuse.pem --f reg.lkr --p password | grep "^  password"
@@ -0,0 +1,13 @@
+#!/bin/bash
+echo -e "move and copy entries:\n"
+cp tests/test.lkr reg.a.lkr
+locker --mv this_is_a_key renamed --k tests/donotuse.pem --f reg.a.lkr --p password
+locker renamed --k tests/donotuse.pem --f reg.a.lkr --p password
+locker --cp renamed --to reg.b.lkr --to_key copied --k tests/donotuse.pem --f reg.a.lkr --p password
+locker copied --k tests/donotuse.pem --f reg.b.lkr --p password
+locker --gen_key reg.other.pem --p other
+locker --mv renamed --to reg.b.lkr --to_pub reg.other.pub --k tests/donotuse.pem --f reg.a.lkr --p password | grep "is not the key"
+locker new_entry abc --k reg.other.pem --f reg.c.lkr --p other
+locker --mv renamed --to reg.c.lkr --k tests/donotuse.pem --f reg.a.lkr --p password
+locker renamed --k tests/donotuse.pem --f reg.a.lkr --p password | grep "is not in lkr file"
+locker renamed --k reg.other.pem --f reg.c.lkr --p other | grep "this_is_a_secret_value"
@@ -0,0 +1,6 @@
+#!/bin/bash
+echo -e "move to the same lkr file by another path:\n"
+cp tests/test.lkr reg.lkr
+locker --mv this_is_a_key renamed --to ./reg.lkr --k tests/donotuse.pem --f reg.lkr --p password
+locker --mv renamed --to $(pwd)/reg.lkr --to_key moved --k tests/donotuse.pem --f reg.lkr --p password
+locker moved --k tests/donotuse.pem --f reg.lkr --p password | grep "this_is_a_secret_value"
@@ -0,0 +1,16 @@
+#!/bin/bash
+echo -e "namespaces:\n"
+cp tests/test.lkr reg.lkr
+locker prod/db/password abc --k tests/donotuse.pem --f reg.lkr --p password
+locker prod/api def --k tests/donotuse.pem --f reg.lkr --p password
+locker --ls --ns prod --k tests/donotuse.pem --f reg.lkr --p password | grep this_is_a_key && exit 1
+locker --export reg.export --ns prod --k tests/donotuse.pem --f reg.lkr --p password
+grep '"db/password"' reg.export
+locker --import reg.export --ns staging --k tests/donotuse.pem --f reg.lkr --p password
+locker staging/db/password --k tests/donotuse.pem --f reg.lkr --p password | grep abc
+echo n | locker --delete_ns prod --k tests/donotuse.pem --f reg.lkr --p password
+locker prod/api --k tests/donotuse.pem --f reg.lkr --p password | grep def
+echo y | locker --delete_ns prod --k tests/donotuse.pem --f reg.lkr --p password
+locker --ls --k tests/donotuse.pem --f reg.lkr --p password | grep prod && exit 1
+locker --delete_ns staging -force --k tests/donotuse.pem --f reg.lkr --p password
+locker --ls --k tests/donotuse.pem --f reg.lkr --p password | grep this_is_a_key
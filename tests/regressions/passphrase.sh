@@ -0,0 +1,14 @@
+#!/bin/bash
+echo -e "passphrase lockers, without a PEM:\n"
+locker this_is_a_key this_is_a_secret_value --f reg.lkr -passphrase --p hunter2
+grep '"kdf"' reg.lkr
+grep '"scrypt"' reg.lkr
+locker this_is_a_key --f reg.lkr --p hunter2 | grep this_is_a_secret_value
+locker this_is_a_key --f reg.lkr --p wrong | grep "incorrect passphrase"
+locker another_key another_value --f reg.lkr --p hunter2
+locker -show_keys --f reg.lkr --p hunter2 | grep another_key
+locker this_is_a_key --f reg.pbkdf2.lkr --kdf pbkdf2 --p hunter2 abc
+grep '"pbkdf2"' reg.pbkdf2.lkr
+locker this_is_a_key --f reg.pbkdf2.lkr --p hunter2 | grep abc
+cp tests/test.lkr reg.rsa.lkr
+locker this_is_a_key --f reg.rsa.lkr -passphrase --p hunter2 | grep "not a passphrase locker"
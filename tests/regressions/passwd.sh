@@ -0,0 +1,6 @@
+#!/bin/bash
+echo -e "new passphrase is not taken from the command line:\n"
+cp tests/test.lkr reg.lkr
+cp tests/donotuse.pem reg.pem
+locker --passwd reg.pem new_password --p password | grep "asked for" && \
+locker this_is_a_key --k reg.pem --p password --f reg.lkr | grep this_is_a_secret_value
@@ -0,0 +1,14 @@
+#!/bin/bash
+echo -e "files are written 0600, a PEM others can read is refused in strict mode:\n"
+locker --gen_key reg.pem --p password
+locker this_is_a_key this_is_a_secret_value --f reg.lkr --k reg.pem --p password
+locker this_is_a_key abc -o --f reg.lkr --k reg.pem --p password
+locker --export reg.exported --f reg.lkr --k reg.pem --p password
+stat -c %a reg.pem reg.lkr reg.lkr.bk reg.exported
+test "$(stat -c %a reg.pem reg.lkr reg.lkr.bk reg.exported | sort -u)" == "600"
+test "$(stat -c %a reg.pub)" == "644"
+chmod 644 reg.pem
+locker this_is_a_key --f reg.lkr --k reg.pem --p password | grep "accessible by others"
+LOCKER_STRICT_PERMISSIONS=1 locker this_is_a_key --f reg.lkr --k reg.pem --p password | grep "refusing"
+LOCKER_STRICT_PERMISSIONS=1 locker this_is_a_key --f reg.lkr --k reg.pem --p password | grep abc
+test $? -ne 0
@@ -2,4 +2,6 @@
 echo -e "re_key:\n"
 cp tests/test.lkr reg.lkr
 locker --re_key gen_key.pem new_password --p password --k tests/donotuse.pem
-locker -show_keys --k gen_key.pem --p new_password --f reg.lkr | grep this_is_a_key
\ No newline at end of file
+locker -show_keys --k gen_key.pem --p new_password --f reg.lkr | grep this_is_a_key
+locker --re_key gen_key_2.pem new_password_2 --p new_password --k gen_key.pem --f reg.lkr | grep "re-keyed 1/1"
+locker this_is_a_key --k gen_key_2.pem --p new_password_2 --f reg.lkr | grep this_is_a_secret_value
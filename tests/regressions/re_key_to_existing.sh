@@ -0,0 +1,7 @@
+#!/bin/bash
+echo -e "re_key to an existing key, gen_key refuses to overwrite:\n"
+cp tests/test.lkr reg.lkr
+locker --gen_key other.pem --p other_password
+locker --gen_key other.pem --p other_password | grep "refusing to overwrite"
+locker --re_key --to other.pem --p password --k tests/donotuse.pem --f reg.lkr | grep "re-keyed 1/1"
+locker this_is_a_key --k other.pem --p other_password --f reg.lkr | grep this_is_a_secret_value
@@ -0,0 +1,7 @@
+#!/bin/bash
+echo -e "add recipient, k, f, p specified:\n"
+cp tests/test.lkr reg.lkr
+locker --gen_key recipient.pem --p other_password
+locker --add_recipient recipient.pub --k tests/donotuse.pem --f reg.lkr --p password
+locker -list_recipients --k tests/donotuse.pem --f reg.lkr --p password | grep "(this key)"
+locker this_is_a_key --k recipient.pem --f reg.lkr --p other_password | grep "this_is_a_secret_value"
@@ -0,0 +1,13 @@
+#!/bin/bash
+echo -e "recovery codes:\n"
+cp tests/test.lkr reg.lkr
+cp tests/donotuse.pem reg.pem
+locker -add_recovery --f reg.lkr --k reg.pem --p password > reg.code
+CODE=$(grep -oE "[A-Z2-7]{4}(-[A-Z2-7]{4}){7}" reg.code)
+grep '"recovery"' reg.lkr
+locker --recover reg.new.pem --recovery_code AAAA-BBBB --p new_password --f reg.lkr | grep "incorrect recovery code"
+locker --recover reg.new.pem --recovery_code $CODE --p new_password --f reg.lkr | grep recovered
+locker this_is_a_key --f reg.lkr --k reg.new.pem --p new_password | grep this_is_a_secret_value
+locker this_is_a_key --f reg.lkr --k reg.pem --p password | grep "this locker belongs to key"
+locker --recover $CODE --p new_password --f reg.lkr | grep "given with --recovery_code"
+locker --recover reg.other.pem --recovery_code $CODE --p new_password --f reg.lkr | grep "no recovery code"
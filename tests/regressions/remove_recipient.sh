@@ -0,0 +1,9 @@
+#!/bin/bash
+echo -e "remove recipient, mark rotation, k, f, p specified:\n"
+cp tests/test.lkr reg.lkr
+locker --gen_key recipient.pem --p other_password
+locker --add_recipient recipient.pub --k tests/donotuse.pem --f reg.lkr --p password
+FINGERPRINT=$(locker -list_recipients --k tests/donotuse.pem --f reg.lkr --p password | grep -v "(this key)")
+locker --remove_recipient $FINGERPRINT -mark_rotation --k tests/donotuse.pem --f reg.lkr --p password
+locker -list_recipients --k tests/donotuse.pem --f reg.lkr --p password | grep -v $FINGERPRINT
+locker -show_keys --k tests/donotuse.pem --f reg.lkr --p password | grep "this_is_a_key (needs rotation)"
@@ -0,0 +1,19 @@
+#!/bin/bash
+echo -e "split keys (3 of 5 shares):\n"
+cp tests/test.lkr reg.lkr
+cp tests/donotuse.pem reg.pem
+locker -split --shares 5 --threshold 3 --f reg.lkr --k reg.pem --p password
+grep '"split"' reg.lkr
+grep LOCKER-SHARE-2-3- reg.lkr.share2
+locker this_is_a_key --f reg.lkr --shares reg.lkr.share1,reg.lkr.share3 | grep "3 of 5 shares are needed"
+locker this_is_a_key --f reg.lkr --shares reg.lkr.share1,$(cat reg.lkr.share3),reg.lkr.share5 | grep this_is_a_secret_value
+cp tests/test.lkr reg.enc.lkr
+locker --gen_key reg.alice.pem --p alice
+locker --gen_key reg.bob.pem --p bob --algorithm x25519
+locker -split --shares 2 --threshold 2 --to reg.alice.pub,reg.bob.pub --f reg.enc.lkr --k reg.pem --p password
+grep LOCKER-ENCRYPTED-SHARE reg.enc.lkr.share1
+locker this_is_a_key --f reg.enc.lkr --shares reg.enc.lkr.share1,reg.enc.lkr.share2 | grep "decrypt it with --open_share"
+locker --open_share reg.enc.lkr.share1 reg.alice.pem --p alice > reg.a
+locker --open_share reg.enc.lkr.share2 reg.bob.pem --p bob > reg.b
+locker this_is_a_key --f reg.enc.lkr --shares $(cat reg.a),$(cat reg.b) | grep this_is_a_secret_value
+locker this_is_a_key --f reg.enc.lkr --k reg.pem --p password 2>&1 | grep "belongs to key"
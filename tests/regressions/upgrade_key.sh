@@ -0,0 +1,9 @@
+#!/bin/bash
+echo -e "upgrade a legacy PEM to PKCS#8, lkr file still readable:\n"
+cp tests/test.lkr reg.lkr
+cp tests/donotuse_legacy.pem reg.pem
+locker this_is_a_key --k reg.pem --p password --f reg.lkr | grep "legacy PEM encryption"
+locker --upgrade_key reg.pem --p password
+grep "BEGIN ENCRYPTED PRIVATE KEY" reg.pem
+locker this_is_a_key --k reg.pem --p password --f reg.lkr | grep this_is_a_secret_value
+locker --upgrade_key reg.pem --p password | grep "already PKCS#8"
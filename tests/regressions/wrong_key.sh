@@ -0,0 +1,6 @@
+#!/bin/bash
+echo -e "show_keys with a key that does not own the lkr file, k, f, p specified:\n"
+cp tests/test.lkr reg.lkr
+locker this_is_a_new_key abc --k tests/donotuse.pem --f reg.lkr --p password
+locker --gen_key other.pem --p other_password
+locker -show_keys --k other.pem --f reg.lkr --p other_password | grep "this locker belongs to key"
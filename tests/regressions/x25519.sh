@@ -0,0 +1,12 @@
+#!/bin/bash
+echo -e "x25519 keys, as the locker key and as a recipient:\n"
+locker --gen_key x.pem --p password --algorithm x25519
+grep "BEGIN ENCRYPTED PRIVATE KEY" x.pem
+locker this_is_a_key this_is_a_secret_value --f x.lkr --k x.pem --p password
+grep '"algorithm": "X25519"' x.lkr
+locker this_is_a_public_key abc --f x.lkr --pub x.pub
+locker this_is_a_public_key --f x.lkr --k x.pem --p password | grep abc
+cp tests/test.lkr reg.lkr
+cp tests/donotuse.pem reg.pem
+locker --add_recipient x.pub --f reg.lkr --k reg.pem --p password
+locker this_is_a_key --f reg.lkr --k x.pem --p password | grep this_is_a_secret_value
@@ -0,0 +1,117 @@
+mod common;
+
+#[cfg(test)]
+mod test_age
+{
+    use crate::common::*;
+    use locker::age::{encrypt, decrypt, is_age, recipient_string, parse_recipient, identity_string, parse_identity, read_identities, bech32_encode, bech32_decode, AgeRecipient, AgeIdentity};
+    use locker::util::{read_bytes, read_file_raw};
+    use openssl::pkey::{PKey, Id};
+
+    fn x25519() -> PKey<openssl::pkey::Private>
+    {
+        PKey::generate_x25519().unwrap()
+    }
+
+    #[test]
+    fn bech32_bip173()
+    {
+        assert_eq!(bech32_decode("A12UEL5L"), Some(("a".to_string(), vec![])));
+        assert_eq!(bech32_decode("a12uel5l"), Some(("a".to_string(), vec![])));
+        let (hrp, data) = bech32_decode("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw").unwrap();
+        assert_eq!(hrp, "abcdef");
+        assert_eq!(bech32_encode(&hrp, &data), "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw");
+
+        for invalid in ["pzry9x0s0muk", "1pzry9x0s0muk", "x1b4n0q5v", "li1dgmt3", "A1G7SGD8", "10a06t8", "1qzzfhee", "A12uEL5L"]
+        {
+            assert_eq!(bech32_decode(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn x25519_rfc7748_keys()
+    {
+        // RFC 7748 section 6.1, Alice's key pair
+        let private = PKey::private_key_from_raw_bytes(&read_bytes("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a".to_string()), Id::X25519).unwrap();
+        let public = read_bytes("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a".to_string());
+
+        let recipient = recipient_string(&PKey::public_key_from_raw_bytes(&private.raw_public_key().unwrap(), Id::X25519).unwrap()).unwrap();
+        assert!(recipient.starts_with("age1"));
+        assert_eq!(bech32_decode(&recipient).unwrap().1, public);
+        assert_eq!(parse_recipient(&recipient).unwrap().raw_public_key().unwrap(), public);
+
+        let identity = identity_string(&private).unwrap();
+        assert!(identity.starts_with("AGE-SECRET-KEY-1"));
+        assert_eq!(parse_identity(&identity).unwrap().raw_private_key().unwrap(), private.raw_private_key().unwrap());
+
+        assert!(parse_recipient(&recipient.to_uppercase()).is_err());
+        assert!(parse_identity(&identity.to_lowercase()).is_err());
+    }
+
+    // written by tests/age_vectors.py, an age encoder independent of
+    //  src/age.rs (python3 tests/age_vectors.py)
+    #[test]
+    fn decrypt_known_files()
+    {
+        let data = read_file_raw("tests/encrypted.age").unwrap();
+        assert!(is_age(&data));
+        let identities: Vec<AgeIdentity> = read_identities("tests/donotuse_age.txt").unwrap().into_iter().map(AgeIdentity::X25519).collect();
+        assert_eq!(decrypt(&identities, &data).unwrap(), TEST_ENCRYPTED_FILE_PLAIN.as_bytes());
+        assert!(decrypt(&[AgeIdentity::X25519(x25519())], &data).is_err());
+
+        let data = read_file_raw("tests/encrypted_scrypt.age").unwrap();
+        assert_eq!(decrypt(&[AgeIdentity::Scrypt(PEM_PASSWORD.to_string())], &data).unwrap(), TEST_ENCRYPTED_FILE_PLAIN.as_bytes());
+        assert!(decrypt(&[AgeIdentity::Scrypt("not the password".to_string())], &data).is_err());
+    }
+
+    #[test]
+    fn x25519_roundtrip()
+    {
+        let a = x25519();
+        let b = x25519();
+        let recipients = vec![AgeRecipient::X25519(PKey::public_key_from_raw_bytes(&a.raw_public_key().unwrap(), Id::X25519).unwrap()), AgeRecipient::X25519(PKey::public_key_from_raw_bytes(&b.raw_public_key().unwrap(), Id::X25519).unwrap())];
+
+        // empty, under one chunk, exactly two chunks and over two chunks
+        for length in [0, TEST_STRING.len(), 2*64*1024, 2*64*1024+1]
+        {
+            let plain: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+            let data = encrypt(&recipients, &plain).unwrap();
+            assert!(is_age(&data));
+            assert_eq!(decrypt(&[AgeIdentity::X25519(a.clone())], &data).unwrap(), plain);
+            assert_eq!(decrypt(&[AgeIdentity::X25519(x25519()), AgeIdentity::X25519(b.clone())], &data).unwrap(), plain);
+        }
+    }
+
+    #[test]
+    fn scrypt_roundtrip()
+    {
+        let data = encrypt(&[AgeRecipient::Scrypt(PEM_PASSWORD.to_string(), 10)], TEST_STRING.as_bytes()).unwrap();
+        assert_eq!(decrypt(&[AgeIdentity::Scrypt(PEM_PASSWORD.to_string())], &data).unwrap(), TEST_STRING.as_bytes());
+
+        let a = x25519();
+        let public = PKey::public_key_from_raw_bytes(&a.raw_public_key().unwrap(), Id::X25519).unwrap();
+        assert!(encrypt(&[AgeRecipient::Scrypt(PEM_PASSWORD.to_string(), 10), AgeRecipient::X25519(public)], TEST_STRING.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn tampered_file()
+    {
+        let a = x25519();
+        let public = PKey::public_key_from_raw_bytes(&a.raw_public_key().unwrap(), Id::X25519).unwrap();
+        let data = encrypt(&[AgeRecipient::X25519(public)], TEST_STRING.as_bytes()).unwrap();
+        let identities = [AgeIdentity::X25519(a)];
+
+        let mut payload = data.clone();
+        let last = payload.len()-1;
+        payload[last] ^= 1;
+        assert!(decrypt(&identities, &payload).is_err());
+
+        let mut header = data.clone();
+        let mac = header.windows(4).position(|w| w == b"--- ").unwrap()+4;
+        header[mac] = if header[mac] == b'A' { b'B' } else { b'A' };
+        assert!(decrypt(&identities, &header).is_err());
+
+        assert!(decrypt(&identities, &data[0..data.len()-1]).is_err());
+        assert!(!is_age(TEST_STRING.as_bytes()));
+    }
+}
@@ -4,8 +4,11 @@ mod common;
 mod test_encrypt_decrypt
 {
     use crate::common::*;
-    use locker::crypto::{encrypt, decrypt, build_rsa};
+    use locker::crypto::{encrypt, decrypt, build_rsa, build_key, build_public, generate_key, change_passphrase, fingerprint, is_legacy_pem, upgrade_key, hkdf_sha256, KeyOptions, PemCipher};
+    use locker::key::KeyAlgorithm;
+    use locker::util::read_bytes;
     use locker::util::read_file_raw;
+    use locker::secret::SecretString;
 
     #[test]
     fn encrypt_decrypt()
@@ -43,4 +46,92 @@ mod test_encrypt_decrypt
 
         assert_eq!(&result[0..3], TEST_ENCRYPTED_FILE_PLAIN.as_bytes());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn generate_key_refuses_overwrite()
+    {
+        let result = generate_key(PEM_PATH, Some(SecretString::new(PEM_PASSWORD.to_string())), false, &KeyOptions::default());
+        assert!(result.is_err());
+        assert!(build_rsa(PEM_PATH, PEM_PASSWORD).is_ok());
+    }
+
+    #[test]
+    fn generate_key_options()
+    {
+        let path = "tests/generate_key_options.pem";
+        let options = KeyOptions { bits: 2048, cipher: PemCipher::Aes128, ..KeyOptions::default() };
+
+        generate_key(path, Some(SecretString::new(PEM_PASSWORD.to_string())), false, &options).unwrap();
+        let rsa = build_rsa(path, PEM_PASSWORD).unwrap();
+        assert_eq!(rsa.size()*8, 2048);
+        assert!(!is_legacy_pem(path));
+
+        let options = KeyOptions { bits: 1024, ..KeyOptions::default() };
+        assert!(generate_key(path, Some(SecretString::new(PEM_PASSWORD.to_string())), true, &options).is_err());
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file("tests/generate_key_options.pub").unwrap();
+    }
+
+    #[test]
+    fn generate_x25519_key()
+    {
+        let path = "tests/generate_x25519_key.pem";
+        let options = KeyOptions { algorithm: KeyAlgorithm::X25519, ..KeyOptions::default() };
+
+        generate_key(path, Some(SecretString::new(PEM_PASSWORD.to_string())), false, &options).unwrap();
+        let key = build_key(path, PEM_PASSWORD).unwrap();
+        let public = build_public("tests/generate_x25519_key.pub").unwrap();
+        assert_eq!(KeyAlgorithm::of(&key), Some(KeyAlgorithm::X25519));
+        assert_eq!(fingerprint(&key), fingerprint(&public));
+        assert!(build_rsa(path, PEM_PASSWORD).is_err());
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file("tests/generate_x25519_key.pub").unwrap();
+    }
+
+    #[test]
+    fn hkdf_rfc5869()
+    {
+        // RFC 5869 A.1
+        let ikm = read_bytes("0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B".to_string());
+        let salt = read_bytes("000102030405060708090A0B0C".to_string());
+        let info = read_bytes("F0F1F2F3F4F5F6F7F8F9".to_string());
+        let okm = read_bytes("3CB25F25FAACD57A90434F64D0362F2A2D2D0A90CF1A5A4C5DB02D56ECC4C5BF34007208D5B887185865".to_string());
+
+        assert_eq!(hkdf_sha256(&salt, &ikm, &info, 42), okm);
+    }
+
+    #[test]
+    fn change_passphrase_keeps_key()
+    {
+        let path = "tests/change_passphrase.pem";
+        std::fs::copy(PEM_PATH, path).unwrap();
+
+        assert!(change_passphrase(path, &SecretString::new("wrong_password".to_string()), &SecretString::new("new_password".to_string())).is_err());
+        change_passphrase(path, &SecretString::new(PEM_PASSWORD.to_string()), &SecretString::new("new_password".to_string())).unwrap();
+
+        assert!(build_rsa(path, PEM_PASSWORD).is_err());
+        let rsa = build_rsa(path, "new_password").unwrap();
+        assert_eq!(fingerprint(&rsa), fingerprint(&build_rsa(PEM_PATH, PEM_PASSWORD).unwrap()));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn upgrade_key_keeps_key()
+    {
+        let path = "tests/upgrade_key.pem";
+        std::fs::copy(LEGACY_PEM_PATH, path).unwrap();
+        assert!(is_legacy_pem(path));
+        assert!(!is_legacy_pem(PEM_PATH));
+
+        upgrade_key(path, &SecretString::new(PEM_PASSWORD.to_string())).unwrap();
+
+        assert!(!is_legacy_pem(path));
+        let rsa = build_rsa(path, PEM_PASSWORD).unwrap();
+        assert_eq!(fingerprint(&rsa), fingerprint(&build_rsa(LEGACY_PEM_PATH, PEM_PASSWORD).unwrap()));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
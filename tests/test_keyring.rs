@@ -0,0 +1,54 @@
+mod common;
+
+#[cfg(test)]
+mod test_keyring
+{
+    use locker::
+    {
+        crypto::{build_rsa_public, fingerprint},
+        keyring::Keyring,
+        util::dump_bytes
+    };
+
+    use crate::common::*;
+
+    fn keyring_dir(name: &str) -> String
+    {
+        let dir = std::env::temp_dir().join(format!("locker-keyring-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn add_find_remove()
+    {
+        let dir = keyring_dir("add_find_remove");
+        let keyring = Keyring::new(&dir);
+        let public = build_rsa_public(PUB_PATH).unwrap();
+        let fp = dump_bytes(&fingerprint(&public));
+
+        keyring.add("test", PEM_PATH, public.clone()).unwrap();
+        assert!(keyring.add("test", PEM_PATH, public).is_err());
+
+        let entries = keyring.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "test");
+        assert_eq!(entries[0].fingerprint, fp);
+        assert!(!entries[0].default);
+
+        let found = keyring.find(std::slice::from_ref(&fp)).unwrap();
+        assert!(found.ends_with("test.pem"));
+        assert!(keyring.find(&["AB".to_string()]).is_none());
+
+        assert!(keyring.default_key().is_none());
+        keyring.set_default("test").unwrap();
+        assert_eq!(keyring.default_key(), Some(found));
+        assert!(keyring.set_default("other").is_err());
+
+        keyring.remove("test").unwrap();
+        assert!(keyring.list().is_empty());
+        assert!(keyring.default_key().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -5,11 +5,18 @@ mod test_lkr
 {
     use locker::
     {
-        crypto::build_rsa,
-        file::Locker,
-        error::KeyCollisionError
+        crypto::{build_rsa, build_rsa_public, public_key, fingerprint, PassphraseKdf},
+        file::{Locker, KeyInfo, LockerDiff, KeyOrder, key_tree, annotated_key_tree, namespace_key, strip_namespace},
+        util::glob_regex,
+        secret::SecretString
     };
 
+    use openssl::{rsa::Rsa, pkey::PKey};
+
+    use regex::Regex;
+
+    use base64::{Engine as _, engine::general_purpose};
+
     use crate::common::*;
 
     const LKR_PATH: &str = "tests/test.lkr";
@@ -78,4 +85,583 @@ mod test_lkr
         let result = lkr.insert(LKR_KEY, INSERTED_VALUE, rsa.clone(), true);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn insert_with_public_key_lkr_file()
+    {
+        let public = build_rsa_public(PUB_PATH).unwrap();
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        lkr.insert(INSERTED_KEY, INSERTED_VALUE, public.clone(), false).unwrap();
+        assert!(lkr.insert(INSERTED_KEY, LKR_VALUE, public.clone(), false).is_err());
+        lkr.insert(INSERTED_KEY, LKR_VALUE, public, true).unwrap();
+
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let keys = lkr.get_keys(rsa.clone());
+        assert_eq!(keys, vec![LKR_KEY.to_string(), INSERTED_KEY.to_string()]);
+
+        let v = lkr.get(INSERTED_KEY, rsa).unwrap();
+        assert_eq!(v, LKR_VALUE);
+    }
+
+    #[test]
+    fn add_recipient_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let other = Rsa::generate(2048).unwrap();
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        lkr.add_recipient(public_key(&other), rsa.clone()).unwrap();
+        assert!(lkr.add_recipient(public_key(&other), rsa.clone()).is_err());
+        assert_eq!(lkr.recipients().len(), 2);
+
+        lkr.insert(INSERTED_KEY, INSERTED_VALUE, public_key(&other), false).unwrap();
+
+        for key in [rsa, other]
+        {
+            let keys = lkr.get_keys(key.clone());
+            assert_eq!(keys, vec![LKR_KEY.to_string(), INSERTED_KEY.to_string()]);
+            assert_eq!(lkr.get(LKR_KEY, key.clone()).unwrap(), LKR_VALUE);
+            assert_eq!(lkr.get(INSERTED_KEY, key.clone()).unwrap(), INSERTED_VALUE);
+            assert!(lkr.recipients().contains(&locker::util::dump_bytes(&fingerprint(&key))));
+        }
+    }
+
+    #[test]
+    fn x25519_lkr_file()
+    {
+        let x25519 = PKey::generate_x25519().unwrap();
+        let other = PKey::generate_x25519().unwrap();
+        let mut lkr: Locker = Locker::new();
+
+        lkr.insert(INSERTED_KEY, INSERTED_VALUE, x25519.clone(), false).unwrap();
+        assert_eq!(lkr.owner_key(), Some(KeyInfo { algorithm: "X25519".to_string(), bits: 253 }));
+        assert!(lkr.weak_keys(3072).is_empty());
+
+        assert_eq!(lkr.get_keys(x25519.clone()), vec![INSERTED_KEY.to_string()]);
+        assert_eq!(lkr.get(INSERTED_KEY, x25519.clone()).unwrap(), INSERTED_VALUE);
+        assert!(lkr.verify_key(&other).is_err());
+        assert!(lkr.get_keys(other).is_empty());
+    }
+
+    #[test]
+    fn deterministic_lkr_file()
+    {
+        let path = "tests/deterministic.lkr";
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+        for i in 0..8
+        {
+            lkr.insert(&format!("{}_{}", INSERTED_KEY, i), INSERTED_VALUE, rsa.clone(), false).unwrap();
+        }
+        lkr.write(path).unwrap();
+
+        // an unchanged locker is written byte for byte the same
+        let mut lkr: Locker = Locker::new();
+        lkr.read(path).unwrap();
+        lkr.write(path).unwrap();
+        let before = std::fs::read_to_string(format!("{}.bk", path)).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), before);
+
+        // entries are sorted by hash, and untouched ones do not change
+        lkr.insert(INSERTED_KEY, INSERTED_VALUE, rsa.clone(), false).unwrap();
+        lkr.write(path).unwrap();
+        let entries = |json: &str| serde_json::from_str::<serde_json::Value>(json).unwrap()["entries"].as_array().unwrap().clone();
+        let (before, after) = (entries(&before), entries(&std::fs::read_to_string(path).unwrap()));
+        assert_eq!(after.len(), before.len()+1);
+        assert!(before.iter().all(|e| after.contains(e)));
+        let hashes: Vec<String> = after.iter().map(|e| e["hash"].to_string()).collect();
+        let mut sorted = hashes.clone();
+        sorted.sort_by_key(|h| general_purpose::STANDARD_NO_PAD.decode(h.trim_matches('"')).unwrap());
+        assert_eq!(hashes, sorted);
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(format!("{}.bk", path)).unwrap();
+    }
+
+    #[test]
+    fn merge_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let read = || { let mut lkr = Locker::new(); lkr.read(LKR_PATH).unwrap(); lkr };
+        let (base, mut ours, mut theirs) = (read(), read(), read());
+
+        ours.insert(INSERTED_KEY, INSERTED_VALUE, rsa.clone(), false).unwrap();
+        theirs.insert("theirs", INSERTED_VALUE, rsa.clone(), false).unwrap();
+        let merged = Locker::merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(merged.get_keys(rsa.clone()).len(), 3);
+        assert_eq!(merged.get(INSERTED_KEY, rsa.clone()).unwrap(), INSERTED_VALUE);
+        assert_eq!(merged.get("theirs", rsa.clone()).unwrap(), INSERTED_VALUE);
+        assert!(merged.describe(rsa.clone(), false).iter().any(|l| l.starts_with("theirs ")));
+        assert!(merged.describe(rsa.clone(), true).contains(&format!("{} = {}", LKR_KEY, LKR_VALUE)));
+
+        // a delete on one side
+        ours.delete(LKR_KEY, rsa.clone()).unwrap();
+        let merged = Locker::merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(merged.get_keys(rsa.clone()).len(), 2);
+        assert!(!merged.contains(LKR_KEY));
+
+        // both sides change one entry
+        let (mut ours, mut theirs) = (read(), read());
+        ours.insert(LKR_KEY, "a", rsa.clone(), true).unwrap();
+        theirs.insert(LKR_KEY, "b", rsa.clone(), true).unwrap();
+        assert!(Locker::merge(&base, &ours, &theirs).is_err());
+
+        // recipients changed on one side, entries on the other
+        let (mut ours, mut theirs) = (read(), read());
+        ours.add_recipient(PKey::generate_x25519().unwrap(), rsa.clone()).unwrap();
+        theirs.insert(INSERTED_KEY, INSERTED_VALUE, rsa.clone(), false).unwrap();
+        assert!(Locker::merge(&base, &ours, &theirs).is_err());
+        assert!(Locker::merge(&base, &ours, &read()).is_ok());
+    }
+
+    #[test]
+    fn diff_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let read = || { let mut lkr = Locker::new(); lkr.read(LKR_PATH).unwrap(); lkr };
+        let (mut a, mut b) = (read(), read());
+        assert_eq!(a.diff(&b, &rsa), LockerDiff::default());
+        assert_eq!(a.modified(LKR_KEY), None);
+
+        a.insert("only_a", INSERTED_VALUE, &rsa, false).unwrap();
+        b.insert("only_b", INSERTED_VALUE, &rsa, false).unwrap();
+        b.insert(LKR_KEY, INSERTED_VALUE, &rsa, true).unwrap();
+        assert!(b.modified(LKR_KEY).is_some());
+
+        let diff = a.diff(&b, &rsa);
+        assert_eq!(diff.only_a, vec!["only_a".to_string()]);
+        assert_eq!(diff.only_b, vec!["only_b".to_string()]);
+        assert_eq!(diff.different, vec![LKR_KEY.to_string()]);
+
+        a.copy_entry(&b, LKR_KEY, LKR_KEY, &rsa, &rsa, true).unwrap();
+        a.copy_entry(&b, "only_b", "only_b", &rsa, &rsa, false).unwrap();
+        assert_eq!(a.get(LKR_KEY, &rsa).unwrap(), INSERTED_VALUE);
+        assert_eq!(a.modified(LKR_KEY), b.modified(LKR_KEY));
+        assert!(a.copy_entry(&b, "only_b", "only_b", &rsa, &rsa, false).is_err());
+        assert_eq!(a.diff(&b, &rsa).only_a, vec!["only_a".to_string()]);
+        assert!(a.diff(&b, &rsa).different.is_empty());
+    }
+
+    #[test]
+    fn move_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let other = PKey::generate_x25519().unwrap();
+        let (path_a, path_b) = ("tests/move_a.lkr", "tests/move_b.lkr");
+
+        let mut a = Locker::new();
+        a.read(LKR_PATH).unwrap();
+        let value = a.get(LKR_KEY, &rsa).unwrap();
+
+        a.rename(LKR_KEY, "renamed", &rsa).unwrap();
+        assert!(a.get(LKR_KEY, &rsa).is_err());
+        assert_eq!(a.get("renamed", &rsa).unwrap(), value.as_str());
+        assert!(a.rename(LKR_KEY, "renamed", &rsa).is_err());
+
+        let mut b = Locker::new();
+        b.copy_entry(&a, "renamed", "moved", &rsa, &other, false).unwrap();
+        assert_eq!(b.get("moved", &other).unwrap(), value.as_str());
+        assert!(b.get("moved", &rsa).is_err());
+        a.delete("renamed", &rsa).unwrap();
+
+        Locker::write_all(&[(&b, path_b), (&a, path_a)]).unwrap();
+        let (mut a, mut b) = (Locker::new(), Locker::new());
+        a.read(path_a).unwrap();
+        b.read(path_b).unwrap();
+        assert!(a.get("renamed", &rsa).is_err());
+        assert_eq!(b.get("moved", &other).unwrap(), value.as_str());
+
+        for path in [path_a, path_b]
+        {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn list_lkr_file()
+    {
+        let identity = PKey::generate_x25519().unwrap();
+        let mut lkr = Locker::new();
+        for key in ["db.user", "api", "db/password"]
+        {
+            lkr.insert(key, INSERTED_VALUE, &identity, false).unwrap();
+        }
+
+        assert_eq!(lkr.list_keys(&identity, None, KeyOrder::Name), vec!["api", "db.user", "db/password"]);
+
+        let db = Regex::new(&glob_regex("db?*")).unwrap();
+        assert_eq!(lkr.list_keys(&identity, Some(&db), KeyOrder::Name), vec!["db.user", "db/password"]);
+        assert!(lkr.list_keys(PKey::generate_x25519().unwrap(), None, KeyOrder::Name).is_empty());
+
+        // entries inserted in the same second are in name order
+        let modified = lkr.list_keys(&identity, None, KeyOrder::Modified);
+        assert_eq!(modified.len(), 3);
+        assert!(modified.windows(2).all(|w| lkr.modified(&w[0]) >= lkr.modified(&w[1])));
+
+        let keys: Vec<String> = ["db.user", "api", "db/password", "db"].iter().map(|k| k.to_string()).collect();
+        assert_eq!(key_tree(&keys), vec!["db", "db/", "  user", "  password", "api"]);
+
+        let note = |k: &str| match k { "db" | "db.user" => " (needs rotation)".to_string(), _ => String::new() };
+        assert_eq!(annotated_key_tree(&keys, note), vec!["db (needs rotation)", "db/", "  user (needs rotation)", "  password", "api"]);
+    }
+
+    #[test]
+    fn namespace_lkr_file()
+    {
+        assert_eq!(namespace_key("prod/db/", "password"), "prod/db/password");
+        assert_eq!(namespace_key("", "password"), "password");
+        assert_eq!(strip_namespace("prod/db/password", "prod"), Some("db/password"));
+        assert_eq!(strip_namespace("prod/db/password", "prod/db/"), Some("password"));
+        assert_eq!(strip_namespace("production/db", "prod"), None);
+        assert_eq!(strip_namespace("prod", "prod"), None);
+
+        let identity = PKey::generate_x25519().unwrap();
+        let mut lkr = Locker::new();
+        for key in ["prod/db/password", "prod/api", "staging/db/password", "production"]
+        {
+            lkr.insert(key, INSERTED_VALUE, &identity, false).unwrap();
+        }
+
+        assert_eq!(lkr.namespace_keys(&identity, "prod"), vec!["prod/api", "prod/db/password"]);
+        assert_eq!(lkr.namespace_keys(&identity, "").len(), 4);
+
+        assert_eq!(lkr.delete_namespace("prod", &identity).unwrap(), vec!["prod/api", "prod/db/password"]);
+        assert!(lkr.delete_namespace("prod", &identity).is_err());
+        assert_eq!(lkr.get_keys(&identity), vec!["staging/db/password", "production"]);
+    }
+
+    #[test]
+    fn structured_lkr_file()
+    {
+        let identity = PKey::generate_x25519().unwrap();
+        let mut lkr = Locker::new();
+        let field = |name: &str, value: &str| (name.to_string(), SecretString::new(value.to_string()));
+
+        lkr.set_fields("db", &[field("user", "admin"), field("password", INSERTED_VALUE)], &identity).unwrap();
+        lkr.set_fields("db", &[field("conn.host", "localhost"), field("user", "root")], &identity).unwrap();
+        assert!(lkr.is_structured("db"));
+
+        assert_eq!(lkr.get_field("db.password", &identity).unwrap(), INSERTED_VALUE);
+        assert_eq!(lkr.get_field("db.user", &identity).unwrap(), "root");
+        assert_eq!(lkr.get_field("db.conn.host", &identity).unwrap(), "localhost");
+        assert_eq!(lkr.get_field("db.conn", &identity).unwrap(), "{\"host\":\"localhost\"}");
+        assert!(lkr.get_field("db.port", &identity).is_err());
+        assert!(lkr.set_fields("db", &[field("user.name", "x")], &identity).is_err());
+
+        // a key containing the separator is found before a field
+        lkr.insert("db.password", "plain", &identity, false).unwrap();
+        assert_eq!(lkr.get_field("db.password", &identity).unwrap(), "plain");
+        assert!(lkr.set_fields("db.password", &[field("a", "b")], &identity).is_err());
+        assert!(lkr.get_fields("db.password", &identity).is_err());
+
+        lkr.rename("db", "database", &identity).unwrap();
+        assert_eq!(lkr.get_field("database.user", &identity).unwrap(), "root");
+    }
+
+    #[test]
+    fn long_value_rsa_lkr_file()
+    {
+        // longer than one 2048 bit RSA block, so sealed in an envelope
+        let identity = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let certificate = "-----BEGIN CERTIFICATE-----\n".to_string() + &"A".repeat(600);
+        let mut lkr = Locker::new();
+        let field = |name: &str, value: &str| (name.to_string(), SecretString::new(value.to_string()));
+
+        lkr.set_fields("tls", &[field("cert", &certificate), field("host", "localhost")], &identity).unwrap();
+        lkr.insert(INSERTED_KEY, &certificate, &identity, false).unwrap();
+        lkr.insert("short", INSERTED_VALUE, &identity, false).unwrap();
+
+        assert_eq!(lkr.get_field("tls.cert", &identity).unwrap(), certificate);
+        assert_eq!(lkr.get(INSERTED_KEY, &identity).unwrap(), certificate);
+        assert_eq!(lkr.get("short", &identity).unwrap(), INSERTED_VALUE);
+    }
+
+    #[test]
+    fn passphrase_lkr_file()
+    {
+        let path = "tests/passphrase.lkr";
+        for kdf in [PassphraseKdf::scrypt(10), PassphraseKdf::pbkdf2(1000)]
+        {
+            let (mut lkr, identity) = Locker::with_passphrase(PEM_PASSWORD, kdf.clone()).unwrap();
+            lkr.insert(INSERTED_KEY, INSERTED_VALUE, &identity, false).unwrap();
+            lkr.write(path).unwrap();
+
+            let mut lkr: Locker = Locker::new();
+            lkr.read(path).unwrap();
+            assert_eq!(lkr.kdf(), Some(kdf.clone()));
+            assert!(lkr.unlock("not the password").is_err());
+
+            let identity = lkr.unlock(PEM_PASSWORD).unwrap();
+            assert_eq!(lkr.get(INSERTED_KEY, &identity).unwrap(), INSERTED_VALUE);
+
+            let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+            lkr.add_recipient(&rsa, &identity).unwrap();
+            assert_eq!(lkr.get(INSERTED_KEY, rsa).unwrap(), INSERTED_VALUE);
+        }
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(format!("{}.bk", path)).unwrap();
+
+        let mut kdf = PassphraseKdf::scrypt(40);
+        assert!(Locker::with_passphrase(PEM_PASSWORD, kdf.clone()).is_err());
+        kdf.iterations = Some(1000);
+        assert!(kdf.derive(PEM_PASSWORD).is_err());
+        assert!(Locker::new().unlock(PEM_PASSWORD).is_err());
+    }
+
+    #[test]
+    fn recovery_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let new = PKey::generate_x25519().unwrap();
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        let code = lkr.add_recovery(&rsa).unwrap();
+        assert!(lkr.has_recovery());
+        assert!(lkr.add_recovery(&rsa).is_err());
+        assert!(lkr.recovery_identity("AAAA-BBBB").is_err());
+
+        let recovery = lkr.recovery_identity(&code.to_lowercase().replace("-", " ")).unwrap();
+        assert_eq!(lkr.get(LKR_KEY, &recovery).unwrap(), LKR_VALUE);
+
+        lkr.recover(&recovery, &new).unwrap();
+        assert!(!lkr.has_recovery());
+        assert_eq!(lkr.owner(), Some(locker::util::dump_bytes(&fingerprint(&new))));
+        assert_eq!(lkr.recipients(), vec![locker::util::dump_bytes(&fingerprint(&new))]);
+        assert_eq!(lkr.get(LKR_KEY, &new).unwrap(), LKR_VALUE);
+        assert!(lkr.get(LKR_KEY, &recovery).is_err());
+        assert!(lkr.get(LKR_KEY, rsa).is_err());
+        assert!(lkr.recovery_identity(&code).is_err());
+    }
+
+    #[test]
+    fn split_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        let shares = lkr.split(&rsa, 5, 3).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert_eq!(lkr.split_key().unwrap().threshold, 3);
+        assert!(lkr.split(&rsa, 5, 3).is_err());
+
+        let key = lkr.unlock_shares(&[shares[4].clone(), shares[0].clone(), shares[2].clone()]).unwrap();
+        assert_eq!(lkr.get(LKR_KEY, &key).unwrap(), LKR_VALUE);
+        assert!(lkr.unlock_shares(&shares[0..2]).is_err());
+
+        // the splitting key alone no longer opens the locker
+        assert!(lkr.get(LKR_KEY, &rsa).is_err());
+        assert!(lkr.verify_key(&rsa).is_err());
+        assert_eq!(lkr.recipients(), vec![locker::util::dump_bytes(&fingerprint(&key))]);
+
+        let other = Locker::new().split(&rsa, 5, 3);
+        assert!(other.is_ok());
+        let mixed = vec![shares[0].clone(), shares[1].clone(), other.unwrap()[2].clone()];
+        assert!(lkr.unlock_shares(&mixed).is_err());
+
+        let mut shared = Locker::new();
+        shared.read(LKR_PATH).unwrap();
+        shared.add_recipient(PKey::generate_x25519().unwrap(), &rsa).unwrap();
+        assert!(shared.split(&rsa, 5, 3).is_err());
+    }
+
+    #[test]
+    fn max_recipients_lkr_file()
+    {
+        let identity = PKey::generate_x25519().unwrap();
+        let mut lkr = Locker::new();
+
+        // identity and 254 others
+        for _ in 0..254
+        {
+            lkr.add_recipient(PKey::generate_x25519().unwrap(), &identity).unwrap();
+        }
+        assert_eq!(lkr.recipients().len(), 255);
+        assert!(lkr.add_recipient(PKey::generate_x25519().unwrap(), &identity).is_err());
+    }
+
+    #[test]
+    fn x25519_recipient_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let x25519 = PKey::generate_x25519().unwrap();
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        lkr.add_recipient(&x25519, rsa.clone()).unwrap();
+        lkr.insert(INSERTED_KEY, INSERTED_VALUE, &x25519, false).unwrap();
+
+        assert_eq!(lkr.get(LKR_KEY, &x25519).unwrap(), LKR_VALUE);
+        assert_eq!(lkr.get(INSERTED_KEY, &x25519).unwrap(), INSERTED_VALUE);
+        assert_eq!(lkr.get(INSERTED_KEY, rsa.clone()).unwrap(), INSERTED_VALUE);
+
+        lkr.remove_recipient(&locker::util::dump_bytes(&fingerprint(&rsa)), &x25519, false).unwrap();
+        assert_eq!(lkr.get(LKR_KEY, &x25519).unwrap(), LKR_VALUE);
+        assert!(lkr.get(LKR_KEY, rsa).is_err());
+    }
+
+    #[test]
+    fn remove_recipient_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let other = Rsa::generate(2048).unwrap();
+        let other_fingerprint = locker::util::dump_bytes(&fingerprint(&other));
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        lkr.add_recipient(public_key(&other), rsa.clone()).unwrap();
+        let sealed = lkr.get_keys(other.clone());
+
+        lkr.remove_recipient(&other_fingerprint, rsa.clone(), true).unwrap();
+        assert!(!lkr.recipients().contains(&other_fingerprint));
+        assert!(lkr.needs_rotation(LKR_KEY));
+        assert_eq!(lkr.get_keys(rsa.clone()), sealed);
+        assert_eq!(lkr.get(LKR_KEY, rsa.clone()).unwrap(), LKR_VALUE);
+
+        let own_fingerprint = locker::util::dump_bytes(&fingerprint(&rsa));
+        assert!(lkr.remove_recipient(&own_fingerprint, rsa.clone(), false).is_err());
+
+        lkr.insert(LKR_KEY, INSERTED_VALUE, rsa.clone(), true).unwrap();
+        assert!(!lkr.needs_rotation(LKR_KEY));
+    }
+
+    #[test]
+    fn group_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let other = Rsa::generate(2048).unwrap();
+        let other_fingerprint = locker::util::dump_bytes(&fingerprint(&other));
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        lkr.add_recipient(public_key(&other), rsa.clone()).unwrap();
+        lkr.add_group("ops", vec![other_fingerprint.clone()]).unwrap();
+        assert!(lkr.add_group("ops", vec![other_fingerprint.clone()]).is_err());
+        assert!(lkr.add_group("none", vec!["AB".to_string()]).is_err());
+        assert_eq!(lkr.groups(), vec![("ops".to_string(), vec![other_fingerprint.clone()])]);
+
+        lkr.insert_in_group(INSERTED_KEY, INSERTED_VALUE, rsa.clone(), false, Some("ops")).unwrap();
+        assert!(lkr.insert_in_group(LKR_KEY, INSERTED_VALUE, rsa.clone(), true, Some("ops")).is_err());
+        assert_eq!(lkr.group_of(INSERTED_KEY), Some("ops".to_string()));
+
+        assert_eq!(lkr.get_keys(rsa.clone()), vec![LKR_KEY.to_string()]);
+        assert_eq!(lkr.hidden_keys(rsa.clone()), 1);
+        assert!(lkr.get(INSERTED_KEY, rsa.clone()).is_err());
+
+        assert_eq!(lkr.get_keys(other.clone()), vec![LKR_KEY.to_string(), INSERTED_KEY.to_string()]);
+        assert_eq!(lkr.get(INSERTED_KEY, other.clone()).unwrap(), INSERTED_VALUE);
+
+        // an entry's group must be in the file
+        let path = "tests/group.lkr";
+        lkr.write(path).unwrap();
+        let renamed = std::fs::read_to_string(path).unwrap().replace("\"name\": \"ops\"", "\"name\": \"dev\"");
+        std::fs::write(path, renamed).unwrap();
+        assert!(Locker::new().read(path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn owner_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let other = Rsa::generate(2048).unwrap();
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        assert_eq!(lkr.owner(), None);
+        assert!(lkr.verify_key(&other).is_ok());
+
+        // inserting with any public key does not make it the owner
+        lkr.insert(INSERTED_KEY, INSERTED_VALUE, public_key(&other), false).unwrap();
+        assert_eq!(lkr.owner(), None);
+        assert!(!lkr.claim_owner(&other));
+
+        assert!(lkr.claim_owner(&rsa));
+        assert_eq!(lkr.owner(), Some(locker::util::dump_bytes(&fingerprint(&rsa))));
+        assert!(lkr.verify_key(&rsa).is_ok());
+
+        let result = lkr.verify_key(&other);
+        assert!(result.is_err());
+        assert!(format!("{}", result.unwrap_err()).contains(&locker::util::dump_bytes(&fingerprint(&other))));
+    }
+
+    #[test]
+    fn owner_key_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let weak = Rsa::generate(2048).unwrap();
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+
+        assert_eq!(lkr.owner_key(), None);
+        lkr.insert(INSERTED_KEY, INSERTED_VALUE, rsa.clone(), false).unwrap();
+        assert!(lkr.claim_owner(&rsa));
+        assert_eq!(lkr.owner_key(), Some(KeyInfo { algorithm: "RSA".to_string(), bits: 4096 }));
+        assert!(lkr.weak_keys(3072).is_empty());
+
+        lkr.start_rotation(&public_key(&weak)).unwrap();
+        while lkr.rekey_next(&rsa, &public_key(&weak)).unwrap().is_some() {}
+
+        assert_eq!(lkr.weak_keys(3072), vec![(locker::util::dump_bytes(&fingerprint(&weak)), 2048)]);
+        assert!(lkr.weak_keys(2048).is_empty());
+    }
+
+    #[test]
+    fn rotation_lkr_file()
+    {
+        let rsa = build_rsa(PEM_PATH, PEM_PASSWORD).unwrap();
+        let other = Rsa::generate(2048).unwrap();
+        let other_fingerprint = locker::util::dump_bytes(&fingerprint(&other));
+        let mut lkr: Locker = Locker::new();
+        lkr.read(LKR_PATH).unwrap();
+        lkr.insert(INSERTED_KEY, INSERTED_VALUE, rsa.clone(), false).unwrap();
+
+        assert!(lkr.rekey_next(&rsa, &public_key(&other)).is_err());
+        lkr.start_rotation(&public_key(&other)).unwrap();
+        assert_eq!(lkr.rotation(), Some(other_fingerprint.clone()));
+        assert!(lkr.start_rotation(&Rsa::generate(2048).unwrap()).is_err());
+
+        assert_eq!(lkr.rekey_next(&rsa, &public_key(&other)).unwrap(), Some((1, 2)));
+        assert_eq!(lkr.get_keys(rsa.clone()).len(), 1);
+        assert_eq!(lkr.get_keys(other.clone()).len(), 1);
+        assert!(lkr.verify_key(&other).is_ok());
+
+        assert_eq!(lkr.rekey_next(&rsa, &public_key(&other)).unwrap(), Some((2, 2)));
+        assert_eq!(lkr.rekey_next(&rsa, &public_key(&other)).unwrap(), None);
+
+        assert_eq!(lkr.rotation(), None);
+        assert_eq!(lkr.owner(), Some(other_fingerprint.clone()));
+        assert_eq!(lkr.key_id(LKR_KEY), Some(other_fingerprint));
+        assert_eq!(lkr.get_keys(other.clone()), vec![LKR_KEY.to_string(), INSERTED_KEY.to_string()]);
+        assert_eq!(lkr.get(INSERTED_KEY, other.clone()).unwrap(), INSERTED_VALUE);
+        assert!(lkr.get(INSERTED_KEY, rsa.clone()).is_err());
+    }
+
+    #[test]
+    fn rotation_with_delete_lkr_file()
+    {
+        let old = PKey::generate_x25519().unwrap();
+        let new = PKey::generate_x25519().unwrap();
+        let mut lkr = Locker::new();
+        for key in ["a", "b", "c", "d"]
+        {
+            lkr.insert(key, INSERTED_VALUE, &old, false).unwrap();
+        }
+
+        lkr.start_rotation(&new).unwrap();
+        assert_eq!(lkr.rekey_next(&old, &new).unwrap(), Some((1, 4)));
+        assert_eq!(lkr.rekey_next(&old, &new).unwrap(), Some((2, 4)));
+
+        // shifts the unrotated entries back past where rotation resumes
+        lkr.delete("a", &new).unwrap();
+        while lkr.rekey_next(&old, &new).unwrap().is_some() {}
+
+        assert_eq!(lkr.rotation(), None);
+        assert_eq!(lkr.get_keys(&new), vec!["b", "c", "d"]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,58 @@
+mod common;
+
+#[cfg(test)]
+mod test_secret
+{
+    use crate::common::*;
+    use locker::secret::{wipe, read_secret, secret_json, SecretBytes, SecretString};
+    use locker::crypto::{seal, try_decrypt_string};
+    use locker::key::Recipient;
+    use openssl::pkey::PKey;
+
+    #[test]
+    fn wipe_zeroes()
+    {
+        let mut bytes = TEST_STRING.as_bytes().to_vec();
+        wipe(&mut bytes);
+        assert!(bytes.iter().all(|b| *b == 0));
+        assert_eq!(bytes.len(), TEST_STRING.len());
+    }
+
+    #[test]
+    fn redacted_debug()
+    {
+        let secret = SecretString::new(TEST_STRING.to_string());
+        assert_eq!(format!("{:?}", secret), "[redacted]");
+        assert_eq!(secret, TEST_STRING);
+        assert_eq!(secret.as_str(), TEST_STRING);
+        assert_eq!(secret.len(), TEST_STRING.len());
+
+        let bytes = SecretBytes::new(TEST_STRING.as_bytes().to_vec());
+        assert_eq!(format!("{:?}", bytes), "[redacted]");
+        assert_eq!(bytes.as_bytes(), TEST_STRING.as_bytes());
+    }
+
+    #[test]
+    fn read_and_serialise_secret()
+    {
+        // longer than the first buffer, so it is grown
+        let long = TEST_STRING.repeat(1000) + "\n";
+        let mut secret = read_secret(&mut long.as_bytes()).unwrap();
+        assert_eq!(secret, long);
+        secret.truncate(long.len()-1);
+        assert_eq!(secret.as_str(), long.trim_end());
+        assert!(read_secret(&mut &[0xff, 0xfe][..]).is_err());
+
+        let value = serde_json::json!({"user": "admin", "conn": {"port": 5432}});
+        assert_eq!(secret_json(&value), value.to_string());
+    }
+
+    #[test]
+    fn decrypt_to_secret()
+    {
+        let identity = PKey::generate_x25519().unwrap();
+        let sealed = seal(&[identity.public()], TEST_STRING.as_bytes());
+        assert_eq!(try_decrypt_string(&sealed, &identity).unwrap(), TEST_STRING);
+        assert!(try_decrypt_string(&sealed, &PKey::generate_x25519().unwrap()).is_none());
+    }
+}
@@ -0,0 +1,64 @@
+mod common;
+
+#[cfg(test)]
+mod test_shamir
+{
+    use locker::shamir::{split, combine, share_string, parse_share};
+
+    const SECRET: &[u8] = b"a 32 byte secret, for a key.....";
+
+    #[test]
+    fn any_threshold_of_shares()
+    {
+        let shares = split(SECRET, 5, 3).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for i in 0..5
+        {
+            for j in i+1..5
+            {
+                for k in j+1..5
+                {
+                    let subset = vec![shares[i].clone(), shares[j].clone(), shares[k].clone()];
+                    assert_eq!(combine(&subset).unwrap(), SECRET);
+                }
+                assert_ne!(combine(&[shares[i].clone(), shares[j].clone()]).unwrap(), SECRET);
+            }
+        }
+
+        assert_eq!(combine(&shares).unwrap(), SECRET);
+        assert!(combine(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err());
+        assert!(combine(&[]).is_err());
+    }
+
+    #[test]
+    fn known_shares()
+    {
+        // f(x) = 0x42 + 0x57x, and 0x57*0x83 = 0xc1 in GF(256) (FIPS 197)
+        let shares = vec![(1, vec![0x42 ^ 0x57]), (0x83, vec![0x42 ^ 0xc1])];
+        assert_eq!(combine(&shares).unwrap(), vec![0x42]);
+    }
+
+    #[test]
+    fn invalid_split()
+    {
+        assert!(split(SECRET, 5, 1).is_err());
+        assert!(split(SECRET, 2, 3).is_err());
+        assert!(split(SECRET, 255, 255).is_ok());
+    }
+
+    #[test]
+    fn share_text()
+    {
+        let shares = split(SECRET, 3, 2).unwrap();
+        let text = share_string(shares[1].0, 2, &shares[1].1);
+        assert!(text.starts_with("LOCKER-SHARE-2-2-"));
+        assert_eq!(parse_share(&text).unwrap(), (2, 2, shares[1].1.clone()));
+        assert_eq!(parse_share(&format!("  {}\n", text)).unwrap(), (2, 2, shares[1].1.clone()));
+
+        for malformed in ["LOCKER-SHARE-0-2-AB", "LOCKER-SHARE-1-2-ABC", "LOCKER-SHARE-1-2-XY", "LOCKER-SHARE-1-2", "SHARE-1-2-AB"]
+        {
+            assert!(parse_share(malformed).is_err(), "{}", malformed);
+        }
+    }
+}
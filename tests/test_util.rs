@@ -6,7 +6,9 @@ mod test_util
     const TEST_STRING: &str = "hello";
     const COMPRESS_STRING: &str = "This is a string that can be compressed. This is a string that can be compressed.";
 
-    use locker::util::{read_file_utf8, read_file_raw, write_file, compress, decompress, dump_bytes};
+    use locker::util::{read_file_utf8, read_file_raw, write_file, write_public_file, accessible_by_others, compress, decompress, glob_regex};
+    use regex::Regex;
+    use locker::crypto::check_pem_permissions;
    
     #[test]
     fn test_read_uft8()
@@ -73,7 +75,7 @@ mod test_util
         assert!(compressed.is_ok());
 
         let compressed_string = compressed.unwrap();
-        assert!(compressed_string.len() < COMPRESS_STRING.as_bytes().len());
+        assert!(compressed_string.len() < COMPRESS_STRING.len());
 
         let decompressed = decompress(compressed_string);
         assert!(decompressed.is_ok());
@@ -81,4 +83,43 @@ mod test_util
         let decompressed_string = decompressed.unwrap();
         assert_eq!(decompressed_string, COMPRESS_STRING);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_glob_regex()
+    {
+        let matches = |glob: &str, s: &str| Regex::new(&glob_regex(glob)).unwrap().is_match(s);
+        assert!(matches("db.*", "db.prod.password"));
+        assert!(!matches("db.*", "dbx"));
+        assert!(!matches("db", "db.password"));
+        assert!(matches("key_?", "key_1"));
+        assert!(!matches("key_?", "key_12"));
+        assert!(matches("key_[12]", "key_2"));
+        assert!(!matches("key_[!12]", "key_2"));
+        assert!(matches("key_[!12]", "key_3"));
+        assert!(matches("a+b(c)", "a+b(c)"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_permissions()
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = |p: &str| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+
+        std::fs::write("tmp_permissions", TEST_STRING).unwrap();
+        std::fs::set_permissions("tmp_permissions", std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(accessible_by_others("tmp_permissions"), Some(0o644));
+        assert!(check_pem_permissions("tmp_permissions", false).is_ok());
+        assert!(check_pem_permissions("tmp_permissions", true).is_err());
+
+        // an existing file is restricted too
+        write_file("tmp_permissions", TEST_STRING.as_bytes());
+        assert_eq!(mode("tmp_permissions"), 0o600);
+        assert_eq!(accessible_by_others("tmp_permissions"), None);
+        assert!(check_pem_permissions("tmp_permissions", true).is_ok());
+
+        write_public_file("tmp_permissions", TEST_STRING.as_bytes());
+        assert_eq!(mode("tmp_permissions"), 0o644);
+        std::fs::remove_file("tmp_permissions").unwrap();
+    }
+}